@@ -0,0 +1,52 @@
+use advent_of_code_2020::baseline::{self, Baseline};
+use advent_of_code_2020::registry;
+use criterion::{black_box, Criterion};
+use std::env;
+use std::fs;
+
+// Times the generator and every registered solver of each day that has an
+// input in input/2020/, then stores the means as a JSON baseline
+// (target/bench-baseline.json, or $AOC_BASELINE).
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+
+    for day in registry::days() {
+        let path = registry::input_path(day);
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(_) => {
+                eprintln!("Skipping day{}: no input at {}", day, path.display());
+                continue;
+            }
+        };
+        let mut group = criterion.benchmark_group(format!("day{}", day));
+        let mut solvers = registry::for_day(day).peekable();
+        if let Some(first) = solvers.peek() {
+            group.bench_function("generator", |b| {
+                b.iter(|| first.generate(black_box(&input)).unwrap())
+            });
+        }
+        for solver in solvers {
+            let runner = solver.generate(&input).unwrap();
+            let id = match solver.variant {
+                Some(variant) => format!("part{} ({})", solver.part, variant),
+                None => format!("part{}", solver.part),
+            };
+            group.bench_function(id, |b| {
+                b.iter(|| {
+                    runner.bench(|answer| {
+                        black_box(answer);
+                    })
+                })
+            });
+        }
+        group.finish();
+    }
+    criterion.final_summary();
+
+    let out = env::var("AOC_BASELINE").unwrap_or_else(|_| baseline::DEFAULT_PATH.to_owned());
+    match Baseline::from_criterion_dir(baseline::CRITERION_DIR).and_then(|b| b.save(&out)) {
+        Ok(()) => println!("Saved baseline to {}", out),
+        Err(err) => eprintln!("Could not save baseline: {:#}", err),
+    }
+}
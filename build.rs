@@ -0,0 +1,174 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=examples");
+    registry();
+    example_tests();
+}
+
+// Builds a runtime table of every `#[aoc(dayN, partM[, Name])]` solver in the
+// modules declared in src/lib.rs. Each entry points at the factory function
+// `aoc_lib!` generates for it, so the table cannot drift from the attributes.
+// The modules' `solution!` types get the same treatment for solution.rs.
+fn registry() {
+    let lib = fs::read_to_string("src/lib.rs").expect("Could not read src/lib.rs");
+    let modules: Vec<&str> = lib
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .strip_prefix("pub mod ")?
+                .strip_suffix(';')
+                .filter(|name| name.starts_with("day"))
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut solutions = Vec::new();
+    for module in modules {
+        let source = fs::read_to_string(format!("src/{}.rs", module))
+            .unwrap_or_else(|_| panic!("Could not read src/{}.rs", module));
+        // The type the module's `solution!` declares comes first.
+        if let Some(start) = source.find("solution!(") {
+            let rest = source[start + "solution!(".len()..].trim_start();
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            solutions.push(format!("    {}::{},\n", module, &rest[..end]));
+        }
+        let mut generator = None;
+        let mut solvers = Vec::new();
+        let mut lines = source.lines().map(|line| line.trim());
+        while let Some(line) = lines.next() {
+            let attribute = match line.strip_prefix("#[") {
+                Some(attribute) if attribute.starts_with("aoc") => attribute,
+                _ => continue,
+            };
+            // The annotated function is the next `fn` after the attribute.
+            let function = lines
+                .find_map(|line| {
+                    let rest = &line[line.find("fn ")? + 3..];
+                    Some(&rest[..rest.find(['(', '<'])?])
+                })
+                .expect("Attribute without a function");
+            if attribute.starts_with("aoc_generator(") {
+                generator = Some(function);
+            } else if let Some(args) = attribute
+                .strip_prefix("aoc(")
+                .and_then(|rest| rest.strip_suffix(")]"))
+            {
+                solvers.push((args, function));
+            }
+        }
+
+        for (args, function) in solvers {
+            let args: Vec<&str> = args.split(',').map(|arg| arg.trim()).collect();
+            let day: u32 = args[0]
+                .trim_start_matches("day")
+                .parse()
+                .expect("Invalid day in #[aoc]");
+            let part: u32 = args[1]
+                .trim_start_matches("part")
+                .parse()
+                .expect("Invalid part in #[aoc]");
+            let variant = args.get(2).copied();
+
+            // Same naming as aoc-runner-derive's to_snakecase/to_camelcase.
+            let (factory, trait_name) = match variant {
+                Some(name) => (
+                    format!("day{}_part{}_{}", day, part, name.to_lowercase()),
+                    format!("Day{}Part{}{}", day, part, name.to_uppercase()),
+                ),
+                None => (
+                    format!("day{}_part{}", day, part),
+                    format!("Day{}Part{}", day, part),
+                ),
+            };
+            entries.push(format!(
+                "    RegisteredSolver {{ day: {}, part: {}, variant: {:?}, module: {:?}, \
+                 function: {:?}, generator: {:?}, \
+                 factory: <crate::Factory as crate::{}>::{} }},\n",
+                day, part, variant, module, function, generator, trait_name, factory
+            ));
+        }
+    }
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("registry.rs");
+    fs::write(
+        out,
+        format!(
+            "static SOLVERS: &[RegisteredSolver] = &[\n{}];\n",
+            entries.concat()
+        ),
+    )
+    .expect("Could not write the solver registry");
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("solutions.rs");
+    fs::write(
+        out,
+        format!(
+            "static SOLUTIONS: &[Entry] = entries![\n{}];\n",
+            solutions.concat()
+        ),
+    )
+    .expect("Could not write the solution table");
+}
+
+// One test per examples/dayNN/<name>.txt, so adding an example file and its
+// expected answers is enough to get it checked, and one more feeding it with
+// CRLF line endings. The parts its answers.toml section lists under `ignore`
+// get a separate `#[ignore]` test.
+fn example_tests() {
+    let mut tests = String::new();
+    let mut days: Vec<_> = fs::read_dir("examples")
+        .map(|dir| dir.filter_map(|entry| Some(entry.ok()?.path())).collect())
+        .unwrap_or_default();
+    days.sort();
+    for day_dir in days {
+        let day: u32 = match day_dir
+            .file_name()
+            .and_then(|name| name.to_str()?.strip_prefix("day")?.parse().ok())
+        {
+            Some(day) => day,
+            None => continue,
+        };
+        let mut files: Vec<_> = fs::read_dir(&day_dir)
+            .expect("Could not read an examples directory")
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension() == Some(OsStr::new("txt")))
+            .collect();
+        files.sort();
+        let manifest: toml::value::Table = fs::read_to_string(day_dir.join("answers.toml"))
+            .map(|content| toml::from_str(&content).expect("Invalid answers.toml"))
+            .unwrap_or_default();
+        for file in files {
+            let name = file.file_stem().unwrap().to_str().unwrap();
+            let test_name: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            for (suffix, crlf) in [("", false), ("_crlf", true)].iter() {
+                tests.push_str(&format!(
+                    "#[test]\nfn day{:02}_{}{}() {{\n    check_example({}, {:?}, {}, false);\n}}\n\n",
+                    day, test_name, suffix, day, name, crlf
+                ));
+            }
+            let ignored = manifest
+                .get(name)
+                .and_then(|answers| answers.get("ignore")?.as_array())
+                .is_some_and(|parts| !parts.is_empty());
+            if ignored {
+                tests.push_str(&format!(
+                    "#[test]\n#[ignore]\nfn day{:02}_{}_ignored() {{\n    check_example({}, {:?}, false, true);\n}}\n\n",
+                    day, test_name, day, name
+                ));
+            }
+        }
+    }
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("example_tests.rs");
+    fs::write(out, tests).expect("Could not write the example tests");
+}
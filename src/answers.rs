@@ -0,0 +1,156 @@
+use crate::input;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_PATH: &str = "answers.toml";
+
+pub fn sha256(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Answers are keyed by the input's SHA-256 so that several inputs for the
+// same day can be tracked without storing the inputs themselves. The input is
+// hashed as the generators see it, so a copy saved with CRLF line endings or a
+// trailing blank line keeps its answers.
+pub fn input_hash(input: &str) -> String {
+    sha256(&input::normalize(input))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredAnswer {
+    pub day: u32,
+    pub part: u32,
+    pub input: String,
+    pub answer: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnswerStore {
+    #[serde(default, rename = "answer")]
+    pub answers: Vec<StoredAnswer>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Correct,
+    Wrong { expected: String },
+    Unknown,
+}
+
+impl Verdict {
+    pub fn mark(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "✓",
+            Verdict::Wrong { .. } => "✗",
+            Verdict::Unknown => "?",
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Wrong { expected } => write!(f, "{} (expected {})", self.mark(), expected),
+            _ => write!(f, "{}", self.mark()),
+        }
+    }
+}
+
+impl AnswerStore {
+    // A missing file is an empty store, so the first `--record` creates it.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(AnswerStore::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid answer store {}", path.display()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, day: u32, part: u32, input: &str) -> Option<&str> {
+        self.answers
+            .iter()
+            .find(|a| a.day == day && a.part == part && a.input == input)
+            .map(|a| a.answer.as_str())
+    }
+
+    pub fn check(&self, day: u32, part: u32, input: &str, answer: &str) -> Verdict {
+        match self.get(day, part, input) {
+            Some(expected) if expected == answer => Verdict::Correct,
+            Some(expected) => Verdict::Wrong {
+                expected: expected.to_owned(),
+            },
+            None => Verdict::Unknown,
+        }
+    }
+
+    // Stores or replaces an answer and returns the previous one.
+    pub fn record(&mut self, day: u32, part: u32, input: &str, answer: &str) -> Option<String> {
+        let previous = match self
+            .answers
+            .iter_mut()
+            .find(|a| a.day == day && a.part == part && a.input == input)
+        {
+            Some(stored) => Some(std::mem::replace(&mut stored.answer, answer.to_owned())),
+            None => {
+                self.answers.push(StoredAnswer {
+                    day,
+                    part,
+                    input: input.to_owned(),
+                    answer: answer.to_owned(),
+                });
+                None
+            }
+        };
+        self.answers
+            .sort_by(|a, b| (a.day, a.part, &a.input).cmp(&(b.day, b.part, &b.input)));
+        previous
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store() {
+        let hash = input_hash("1721\n979\n366\n299\n675\n1456\n");
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, input_hash("1721\n979\n366\n299\n675\n1457\n"));
+        assert_eq!(
+            hash,
+            input_hash("\u{feff}1721\r\n979\r\n366\r\n299\r\n675\r\n1456\r\n\r\n")
+        );
+
+        let mut store = AnswerStore::default();
+        assert_eq!(store.check(1, 1, &hash, "514579"), Verdict::Unknown);
+        assert_eq!(store.record(1, 2, &hash, "241861950"), None);
+        assert_eq!(store.record(1, 1, &hash, "514579"), None);
+        assert_eq!(store.check(1, 1, &hash, "514579"), Verdict::Correct);
+        assert_eq!(
+            store.check(1, 2, &hash, "1").to_string(),
+            "✗ (expected 241861950)"
+        );
+
+        let reloaded: AnswerStore = toml::from_str(&toml::to_string(&store).unwrap()).unwrap();
+        assert_eq!(reloaded, store);
+        assert_eq!(reloaded.answers[0].part, 1);
+        assert_eq!(
+            toml::from_str::<AnswerStore>("").unwrap(),
+            AnswerStore::default()
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CRITERION_DIR: &str = "target/criterion";
+pub const DEFAULT_PATH: &str = "target/bench-baseline.json";
+
+// Mean time in nanoseconds per benchmark id, e.g. "day11/part2 (parallel2)".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub benchmarks: BTreeMap<String, f64>,
+}
+
+#[derive(Deserialize)]
+struct BenchmarkId {
+    full_id: String,
+}
+
+#[derive(Deserialize)]
+struct Estimate {
+    point_estimate: f64,
+}
+
+#[derive(Deserialize)]
+struct Estimates {
+    mean: Estimate,
+}
+
+fn find_runs(dir: &Path, runs: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name() == Some(OsStr::new("new")) {
+            if path.join("benchmark.json").is_file() {
+                runs.push(path);
+            }
+        } else {
+            find_runs(&path, runs)?;
+        }
+    }
+    Ok(())
+}
+
+impl Baseline {
+    // Collects the latest run of every benchmark criterion has stored.
+    pub fn from_criterion_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut runs = Vec::new();
+        find_runs(dir.as_ref(), &mut runs)
+            .with_context(|| format!("Could not read {}", dir.as_ref().display()))?;
+        let mut benchmarks = BTreeMap::new();
+        for run in runs {
+            let id: BenchmarkId =
+                serde_json::from_str(&fs::read_to_string(run.join("benchmark.json"))?)?;
+            let estimates: Estimates =
+                serde_json::from_str(&fs::read_to_string(run.join("estimates.json"))?)?;
+            benchmarks.insert(id.full_id, estimates.mean.point_estimate);
+        }
+        Ok(Baseline { benchmarks })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Could not read {}", path.as_ref().display()))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Every benchmark present in both baselines; threshold is the tolerated
+    // slowdown, 0.1 meaning 10%.
+    pub fn compare(&self, new: &Baseline, threshold: f64) -> Vec<Change> {
+        self.benchmarks
+            .iter()
+            .filter_map(|(id, old)| {
+                new.benchmarks.get(id).map(|new| Change {
+                    id: id.clone(),
+                    old: *old,
+                    new: *new,
+                    regression: *new > *old * (1.0 + threshold),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub id: String,
+    pub old: f64,
+    pub new: f64,
+    pub regression: bool,
+}
+
+impl Change {
+    pub fn ratio(&self) -> f64 {
+        self.new / self.old
+    }
+}
+
+pub fn format_duration(nanos: f64) -> String {
+    if nanos >= 1e9 {
+        format!("{:.2} s", nanos / 1e9)
+    } else if nanos >= 1e6 {
+        format!("{:.2} ms", nanos / 1e6)
+    } else if nanos >= 1e3 {
+        format!("{:.2} µs", nanos / 1e3)
+    } else {
+        format!("{:.0} ns", nanos)
+    }
+}
+
+pub fn parse_threshold(s: &str) -> Result<f64> {
+    let threshold = match s.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>()? / 100.0,
+        None => s.parse::<f64>()?,
+    };
+    if threshold < 0.0 {
+        return Err(anyhow!("Threshold cannot be negative"));
+    }
+    Ok(threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare() {
+        let old = Baseline {
+            benchmarks: vec![
+                ("day1/part1".to_owned(), 100.0),
+                ("day1/part2".to_owned(), 100.0),
+                ("day2/part1".to_owned(), 100.0),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let new = Baseline {
+            benchmarks: vec![
+                ("day1/part1".to_owned(), 109.0),
+                ("day1/part2".to_owned(), 120.0),
+                ("day3/part1".to_owned(), 100.0),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let changes = old.compare(&new, parse_threshold("10%").unwrap());
+        assert_eq!(changes.len(), 2);
+        assert!(!changes[0].regression);
+        assert!(changes[1].regression);
+        assert!((changes[1].ratio() - 1.2).abs() < 1e-9);
+        assert_eq!(format_duration(1_500_000.0), "1.50 ms");
+    }
+}
@@ -0,0 +1,73 @@
+use advent_of_code_2020::baseline::{self, format_duration, parse_threshold, Baseline};
+use anyhow::{anyhow, Result};
+use std::env;
+use std::process;
+
+const USAGE: &str = "Usage: bench-compare <baseline.json> [<new.json>] [--threshold 10%]
+
+Compares a saved baseline against a newer one (default: the latest criterion
+results in target/criterion) and exits with 1 if anything got slower than
+the threshold.";
+
+fn run() -> Result<bool> {
+    let mut paths = Vec::new();
+    let mut threshold = 0.1;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threshold" | "-t" => {
+                threshold = parse_threshold(&args.next().ok_or_else(|| anyhow!(USAGE))?)?
+            }
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return Ok(true);
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let (old, new) = match paths.as_slice() {
+        [old] => (
+            Baseline::load(old)?,
+            Baseline::from_criterion_dir(baseline::CRITERION_DIR)?,
+        ),
+        [old, new] => (Baseline::load(old)?, Baseline::load(new)?),
+        _ => return Err(anyhow!(USAGE)),
+    };
+
+    let changes = old.compare(&new, threshold);
+    let width = changes.iter().map(|c| c.id.len()).max().unwrap_or(0);
+    for change in changes.iter() {
+        println!(
+            "{:<width$}  {:>10} -> {:>10}  {:>+7.1}%{}",
+            change.id,
+            format_duration(change.old),
+            format_duration(change.new),
+            (change.ratio() - 1.0) * 100.0,
+            if change.regression {
+                "  REGRESSION"
+            } else {
+                ""
+            },
+            width = width
+        );
+    }
+    let regressions = changes.iter().filter(|c| c.regression).count();
+    println!(
+        "{} benchmarks compared, {} regressions above {:.0}%",
+        changes.len(),
+        regressions,
+        threshold * 100.0
+    );
+    Ok(regressions == 0)
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("{:#}", err);
+            process::exit(2);
+        }
+    }
+}
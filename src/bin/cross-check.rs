@@ -0,0 +1,133 @@
+use advent_of_code_2020::answers::{self, AnswerStore, Verdict};
+use advent_of_code_2020::baseline::format_duration;
+use advent_of_code_2020::crosscheck::{self, PartCheck};
+use advent_of_code_2020::registry;
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "Usage: cross-check [<day>...] [--input PATH] [--answers PATH] [--record]
+
+Runs every registered solver of each part on the same input and exits with 1
+if an alternate solver disagrees with the primary one, fails, or contradicts
+the answer store (answers.toml). Without days, checks every day that has an
+input in input/2020/. --record saves the agreed answer of every part the
+store does not know yet.";
+
+// Returns whether every solver agreed and matched the store.
+fn report(check: &PartCheck, store: &AnswerStore, hash: &str) -> bool {
+    let agrees = check.agrees();
+    println!(
+        "day{} part{}: {}",
+        check.day,
+        check.part,
+        if agrees { "ok" } else { "MISMATCH" }
+    );
+    let width = check
+        .outcomes
+        .iter()
+        .map(|o| o.solver.variant.unwrap_or("(default)").len())
+        .max()
+        .unwrap_or(0);
+    let mut correct = true;
+    for outcome in check.outcomes.iter() {
+        let answer = match &outcome.answer {
+            Ok(answer) => {
+                let verdict = store.check(check.day, check.part, hash, answer);
+                correct &= !matches!(verdict, Verdict::Wrong { .. });
+                format!("{} {}", answer, verdict)
+            }
+            Err(err) => format!("error: {}", err),
+        };
+        println!(
+            "  {:<width$}  {:>10}  {:>10}  {}",
+            outcome.solver.variant.unwrap_or("(default)"),
+            format_duration(outcome.generate_time.as_nanos() as f64),
+            format_duration(outcome.solve_time.as_nanos() as f64),
+            answer,
+            width = width
+        );
+    }
+    agrees && correct
+}
+
+fn run() -> Result<bool> {
+    let mut days = Vec::new();
+    let mut input_path = None;
+    let mut answers_path = answers::DEFAULT_PATH.to_owned();
+    let mut record = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input_path = Some(args.next().ok_or_else(|| anyhow!(USAGE))?),
+            "--answers" => answers_path = args.next().ok_or_else(|| anyhow!(USAGE))?,
+            "--record" => record = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return Ok(true);
+            }
+            day => days.push(
+                day.trim_start_matches("day")
+                    .parse::<u32>()
+                    .with_context(|| format!("Invalid day {:?}\n\n{}", day, USAGE))?,
+            ),
+        }
+    }
+    let explicit = !days.is_empty();
+    if !explicit {
+        days = registry::days();
+    } else if input_path.is_some() && days.len() > 1 {
+        return Err(anyhow!("--input only works with a single day"));
+    }
+
+    let mut store = AnswerStore::load(&answers_path)?;
+    let mut recorded = 0;
+    let mut all_agree = true;
+    for day in days {
+        if registry::for_day(day).next().is_none() {
+            return Err(anyhow!("No solvers registered for day{}", day));
+        }
+        let path = input_path
+            .clone()
+            .map(Into::into)
+            .unwrap_or_else(|| registry::input_path(day));
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(err) if explicit => {
+                return Err(err).with_context(|| format!("Could not read {}", path.display()))
+            }
+            Err(_) => {
+                eprintln!("Skipping day{}: no input at {}", day, path.display());
+                continue;
+            }
+        };
+        let hash = answers::input_hash(&input);
+        for check in crosscheck::check_day(day, &input) {
+            let ok = report(&check, &store, &hash);
+            all_agree &= ok;
+            if let (true, true, Ok(answer)) = (record, ok, &check.primary().answer) {
+                if store.get(day, check.part, &hash).is_none() {
+                    store.record(day, check.part, &hash, answer);
+                    recorded += 1;
+                }
+            }
+        }
+    }
+    if recorded > 0 {
+        store.save(&answers_path)?;
+        println!("Recorded {} new answers in {}", recorded, answers_path);
+    }
+    Ok(all_agree)
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("{:#}", err);
+            process::exit(2);
+        }
+    }
+}
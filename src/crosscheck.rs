@@ -0,0 +1,124 @@
+use crate::registry::{self, RegisteredSolver};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub solver: &'static RegisteredSolver,
+    // The answer as displayed, or the generator/solver error (panics included).
+    pub answer: Result<String, String>,
+    pub generate_time: Duration,
+    pub solve_time: Duration,
+}
+
+#[derive(Debug)]
+pub struct PartCheck {
+    pub day: u32,
+    pub part: u32,
+    // The unnamed solver, if there is one, comes first and is the reference
+    // for the others.
+    pub outcomes: Vec<Outcome>,
+}
+
+impl PartCheck {
+    pub fn primary(&self) -> &Outcome {
+        &self.outcomes[0]
+    }
+
+    // Every outcome that failed or differs from the primary answer.
+    pub fn disagreements(&self) -> Vec<&Outcome> {
+        let primary = &self.primary().answer;
+        self.outcomes
+            .iter()
+            .filter(|o| o.answer.is_err() || o.answer != *primary)
+            .collect()
+    }
+
+    pub fn agrees(&self) -> bool {
+        self.disagreements().is_empty()
+    }
+
+    // Only parts with at least one alternate solver actually check anything.
+    pub fn has_variants(&self) -> bool {
+        self.outcomes.len() > 1
+    }
+}
+
+pub(crate) fn describe_panic(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => format!("panicked: {}", message),
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => format!("panicked: {}", message),
+            Err(_) => "panicked".to_owned(),
+        },
+    }
+}
+
+pub fn run_solver(solver: &'static RegisteredSolver, input: &str) -> Outcome {
+    let start = Instant::now();
+    let runner = panic::catch_unwind(|| solver.generate(input).map_err(|e| e.to_string()))
+        .unwrap_or_else(|payload| Err(describe_panic(payload)));
+    let generate_time = start.elapsed();
+
+    let start = Instant::now();
+    let answer = runner.and_then(|runner| {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            runner
+                .try_run()
+                .map(|answer| answer.to_string())
+                .map_err(|e| e.to_string())
+        }))
+        .unwrap_or_else(|payload| Err(describe_panic(payload)))
+    });
+    Outcome {
+        solver,
+        answer,
+        generate_time,
+        solve_time: start.elapsed(),
+    }
+}
+
+pub fn check_part(day: u32, part: u32, input: &str) -> Option<PartCheck> {
+    let mut solvers: Vec<&'static RegisteredSolver> =
+        registry::for_day(day).filter(|s| s.part == part).collect();
+    if solvers.is_empty() {
+        return None;
+    }
+    solvers.sort_by_key(|s| s.variant.is_some());
+    Some(PartCheck {
+        day,
+        part,
+        outcomes: solvers.into_iter().map(|s| run_solver(s, input)).collect(),
+    })
+}
+
+pub fn check_day(day: u32, input: &str) -> Vec<PartCheck> {
+    let mut parts: Vec<u32> = registry::for_day(day).map(|s| s.part).collect();
+    parts.sort_unstable();
+    parts.dedup();
+    parts
+        .into_iter()
+        .filter_map(|part| check_part(day, part, input))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#\n";
+
+    #[test]
+    fn cross_check() {
+        let checks = check_day(3, SAMPLE);
+        assert_eq!(checks.len(), 2);
+        assert!(!checks[0].has_variants());
+        assert!(checks[1].has_variants() && checks[1].agrees());
+        assert_eq!(checks[1].primary().solver.variant, None);
+        assert_eq!(checks[1].outcomes[1].answer, Ok("336".to_owned()));
+
+        let broken = check_part(1, 1, "1721\nnot a number\n").unwrap();
+        assert!(!broken.agrees());
+        assert!(check_part(3, 3, SAMPLE).is_none());
+    }
+}
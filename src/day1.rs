@@ -1,60 +1,166 @@
+use crate::solution::Explanation;
 use aoc_runner_derive::aoc;
-use std::collections::HashSet;
-use std::iter::FromIterator;
+use std::collections::HashMap;
+
+pub const TARGET: i64 = 2020;
+
+pub fn parse_entries(input: &str) -> Vec<i64> {
+    input
+        .lines()
+        .filter_map(|s| s.trim().parse::<i64>().ok())
+        .collect()
+}
+
+// Indices (ascending) of k distinct entries summing up to target.
+pub fn find_k_sum(entries: &[i64], target: i64, k: usize) -> Option<Vec<usize>> {
+    match k {
+        0 => {
+            if target == 0 {
+                Some(Vec::new())
+            } else {
+                None
+            }
+        }
+        1 => entries.iter().position(|x| *x == target).map(|i| vec![i]),
+        2 => {
+            let mut seen: HashMap<i64, usize> = HashMap::new();
+            for (i, x) in entries.iter().enumerate() {
+                if let Some(j) = seen.get(&(target - x)) {
+                    return Some(vec![*j, i]);
+                }
+                seen.entry(*x).or_insert(i);
+            }
+            None
+        }
+        _ => {
+            let mut order: Vec<usize> = (0..entries.len()).collect();
+            order.sort_by_key(|i| entries[*i]);
+            let mut chosen = Vec::with_capacity(k);
+            if k_sum_sorted(entries, &order, 0, target, k, &mut chosen) {
+                chosen.sort_unstable();
+                Some(chosen)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// Fixes one entry per level until two are left, then closes in from both ends.
+fn k_sum_sorted(
+    entries: &[i64],
+    order: &[usize],
+    from: usize,
+    target: i64,
+    k: usize,
+    chosen: &mut Vec<usize>,
+) -> bool {
+    if order.len() < from + k {
+        return false;
+    }
+    if k == 2 {
+        let (mut lo, mut hi) = (from, order.len() - 1);
+        while lo < hi {
+            let sum = entries[order[lo]] + entries[order[hi]];
+            if sum == target {
+                chosen.push(order[lo]);
+                chosen.push(order[hi]);
+                return true;
+            } else if sum < target {
+                lo += 1;
+            } else {
+                hi -= 1;
+            }
+        }
+        return false;
+    }
+    for i in from..=order.len() - k {
+        // Equal values lead to the same sums, the first one covers them all.
+        if i > from && entries[order[i]] == entries[order[i - 1]] {
+            continue;
+        }
+        chosen.push(order[i]);
+        if k_sum_sorted(
+            entries,
+            order,
+            i + 1,
+            target - entries[order[i]],
+            k - 1,
+            chosen,
+        ) {
+            return true;
+        }
+        chosen.pop();
+    }
+    false
+}
+
+pub fn k_sum_product(entries: &[i64], target: i64, k: usize) -> Option<i64> {
+    find_k_sum(entries, target, k).map(|indices| indices.iter().map(|i| entries[*i]).product())
+}
 
 #[aoc(day1, part1, Chars)]
-pub fn part1(input: &str) -> u32 {
-    let entries = HashSet::<u32>::from_iter(
-        input
-            .lines()
-            .into_iter()
-            .filter_map(|s| s.trim().parse::<u32>().ok()),
-    );
-    entries
-        .iter()
-        .find_map(|x| entries.get(&(2020 - x)).map(|y| x * y))
-        .unwrap()
-        .clone()
+pub fn part1(input: &str) -> i64 {
+    k_sum_product(&parse_entries(input), TARGET, 2).expect("No two entries sum to 2020")
 }
+
 #[aoc(day1, part2, Chars)]
-pub fn part2(input: &str) -> u32 {
-    let entries = HashSet::<u32>::from_iter(
-        input
-            .lines()
-            .into_iter()
-            .filter_map(|s| s.trim().parse::<u32>().ok()),
-    );
-    entries
-        .iter()
-        .by_ref()
-        .find_map(|x| {
-            entries
-                .iter()
-                .filter(|y| *y <= x && (x + *y) <= 2020)
-                .find_map(|y| entries.get(&(2020 - (x + y))).map(|z| x * y * z))
-        })
-        .unwrap()
-        .clone()
+pub fn part2(input: &str) -> i64 {
+    k_sum_product(&parse_entries(input), TARGET, 3).expect("No three entries sum to 2020")
+}
+
+// Part N combines N + 1 entries.
+fn explain(part: u32, input: &str) -> Option<Explanation> {
+    let entries = parse_entries(input);
+    let indices = find_k_sum(&entries, TARGET, part as usize + 1)?;
+    Some(Explanation::Entries(
+        indices.iter().map(|i| entries[*i]).collect(),
+    ))
 }
 
+solution!(
+    Day1,
+    1,
+    String,
+    |input: &str| Ok(input.to_owned()),
+    part1,
+    part2,
+    1 "Chars" => part1,
+    2 "Chars" => part2,
+    explain = explain,
+);
+
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::*;
 
-    const sample: &str = "1721
-    979
-    366
-    299
-    675
-    1456";
+    const SAMPLE: &str = include_str!("../examples/day01/sample.txt");
 
     #[test]
     fn sample1() {
-        assert_eq!(part1(&sample), 514579);
+        assert_eq!(part1(&SAMPLE), 514579);
     }
 
     #[test]
     fn sample2() {
-        assert_eq!(part2(&sample), 241861950);
+        assert_eq!(part2(&SAMPLE), 241861950);
+    }
+
+    #[test]
+    fn k_sum() {
+        let entries = parse_entries(SAMPLE);
+        assert_eq!(find_k_sum(&entries, 2020, 2), Some(vec![0, 3]));
+        assert_eq!(find_k_sum(&entries, 2020, 3), Some(vec![1, 2, 4]));
+        assert_eq!(find_k_sum(&entries, 1, 2), None);
+        assert_eq!(
+            find_k_sum(&entries, 1721 + 979 + 366 + 299, 4),
+            Some(vec![0, 1, 2, 3])
+        );
+
+        // A single 1010 must not be used twice.
+        assert_eq!(find_k_sum(&[1010, 5], 2020, 2), None);
+        assert_eq!(find_k_sum(&[1010, 5, 1010], 2020, 2), Some(vec![0, 2]));
+        assert_eq!(find_k_sum(&[3, 1, 3, 3], 9, 3), Some(vec![0, 2, 3]));
+        assert_eq!(find_k_sum(&[3, 1, 3], 9, 3), None);
     }
 }
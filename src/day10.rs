@@ -1,24 +1,142 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterChain {
+    // Outlet (0), sorted adapters and the device.
+    pub joltages: Vec<u64>,
+    pub max_gap: u64,
+}
+
+impl AdapterChain {
+    pub fn new(adapters: &[u64], max_gap: u64) -> Result<Self> {
+        let mut joltages = Vec::with_capacity(adapters.len() + 2);
+        joltages.push(0);
+        joltages.extend_from_slice(adapters);
+        joltages.sort_unstable();
+        joltages.push(joltages.last().unwrap() + max_gap);
+        AdapterChain::from_joltages(joltages, max_gap)
+    }
+
+    pub fn from_joltages(joltages: Vec<u64>, max_gap: u64) -> Result<Self> {
+        if max_gap == 0 {
+            return Err(anyhow!("The maximum gap has to be at least 1"));
+        }
+        if let Some(pair) = joltages.windows(2).find(|pair| pair[1] == pair[0]) {
+            return Err(anyhow!("There are two adapters of {} jolts", pair[0]));
+        }
+        if let Some(pair) = joltages
+            .windows(2)
+            .find(|pair| pair[1] < pair[0] || pair[1] - pair[0] > max_gap)
+        {
+            return Err(anyhow!(
+                "Cannot get from {} to {} jolts with gaps of at most {}",
+                pair[0],
+                pair[1],
+                max_gap
+            ));
+        }
+        Ok(AdapterChain { joltages, max_gap })
+    }
+
+    fn last(&self) -> usize {
+        self.joltages.len() - 1
+    }
+
+    // Indices reachable in one step from i.
+    fn next(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let max = self.joltages[i] + self.max_gap;
+        (i + 1..self.joltages.len()).take_while(move |j| self.joltages[*j] <= max)
+    }
+
+    // ways[i] is the number of ways to get from adapter i to the device.
+    fn ways_to_device<T>(&self) -> Vec<T>
+    where
+        T: Zero + One + Clone,
+    {
+        let mut ways = vec![T::zero(); self.joltages.len()];
+        ways[self.last()] = T::one();
+        for i in (0..self.last()).rev() {
+            ways[i] = self.next(i).fold(T::zero(), |acc, j| acc + ways[j].clone());
+        }
+        ways
+    }
+
+    pub fn count<T>(&self) -> T
+    where
+        T: Zero + One + Clone,
+    {
+        self.ways_to_device::<T>().swap_remove(0)
+    }
+
+    // The n-th arrangement, in lexicographic order of the joltage sequences.
+    // Drawing n uniformly from 0..count() samples arrangements uniformly.
+    pub fn nth_arrangement(&self, n: &BigUint) -> Option<Vec<u64>> {
+        let ways = self.ways_to_device::<BigUint>();
+        if *n >= ways[0] {
+            return None;
+        }
+        let mut n = n.clone();
+        let mut i = 0;
+        let mut arrangement = vec![self.joltages[0]];
+        while i != self.last() {
+            for j in self.next(i) {
+                if n < ways[j] {
+                    i = j;
+                    break;
+                }
+                n -= &ways[j];
+            }
+            arrangement.push(self.joltages[i]);
+        }
+        Some(arrangement)
+    }
+
+    pub fn arrangements(&self) -> impl Iterator<Item = Vec<u64>> + '_ {
+        let mut n = BigUint::zero();
+        std::iter::from_fn(move || {
+            let arrangement = self.nth_arrangement(&n)?;
+            n += 1u32;
+            Some(arrangement)
+        })
+    }
+
+    // Adapters present in every arrangement, i.e. the ones whose neighbours
+    // are too far apart to skip them.
+    pub fn mandatory(&self) -> Vec<u64> {
+        (1..self.last())
+            .filter(|i| self.joltages[i + 1] - self.joltages[i - 1] > self.max_gap)
+            .map(|i| self.joltages[i])
+            .collect()
+    }
+}
+
+pub fn parse_adapters(input: &str) -> Result<Vec<u64>> {
+    let adapters = input
+        .lines()
+        .map(|x| {
+            x.trim()
+                .parse::<u64>()
+                .with_context(|| format!("Invalid adapter {:?}", x))
+        })
+        .collect::<Result<Vec<u64>>>()?;
+    Ok(AdapterChain::new(&adapters, 3)?.joltages)
+}
 
 #[aoc_generator(day10)]
 pub fn input_generator(input: &str) -> Vec<u64> {
-    let mut input: Vec<u64> = input.lines().map(|x| x.parse::<u64>().unwrap()).collect();
-    input.push(0);
-    input.sort();
-    input.push(input.last().unwrap() + 3);
-    input
+    parse_adapters(input).expect("Adapters cannot be chained")
 }
 
-
 #[aoc(day10, part1)]
-pub fn part1(input: &Vec<u64>) -> u64 {
+pub fn part1(input: &[u64]) -> u64 {
+    let chain = AdapterChain::from_joltages(input.to_vec(), 3).expect("Adapters cannot be chained");
     let mut differences = vec![0u64; 3];
-    // dbg!(&input);
-    input.windows(2).for_each(|x| {
+    chain.joltages.windows(2).for_each(|x| {
         let difference = x[1] - x[0];
-        // dbg!(&differences, &difference);
         differences[(difference - 1) as usize] = differences
             .get((difference - 1) as usize)
             .unwrap_or(&0)
@@ -29,100 +147,32 @@ pub fn part1(input: &Vec<u64>) -> u64 {
 }
 
 #[aoc(day10, part2)]
-pub fn part2(input: &Vec<u64>) -> u64 {
-    let mut i = input.len() - 1;
-    let mut arrangements: Vec<u64> = vec![0; input.len()];
-    arrangements[input.len() - 1] = 1;
-    loop {
-        i -= 1;
-
-        let z = match input.len() - i - 1 {
-            0 => unimplemented!(),
-            1 => (input[i + 1] - input[i], 99, 99),
-            2 => (input[i + 1] - input[i], input[i + 2] - input[i], 99),
-            _ => (
-                input[i + 1] - input[i],
-                input[i + 2] - input[i],
-                input[i + 3] - input[i],
-            ),
-        };
-
-        match z {
-            (1, 2, 3) => {
-                arrangements[i] = arrangements[i + 1] + arrangements[i + 2] + arrangements[i + 3]
-            }
-            (1, 2, _) | (1, 3, _) | (2, 3, _) => {
-                arrangements[i] = arrangements[i + 1] + arrangements[i + 2]
-            }
-            (1, _, _) | (2, _, _) | (3, _, _) => arrangements[i] = arrangements[i + 1],
-            _ => panic!("missing pattern {:?}", z),
-        };
-
-        if i == 0 {
-            break;
-        }
-    }
-    return arrangements[0];
+pub fn part2(input: &[u64]) -> BigUint {
+    AdapterChain::from_joltages(input.to_vec(), 3)
+        .expect("Adapters cannot be chained")
+        .count::<BigUint>()
 }
 
-#[aoc(day10, part1)]
-pub fn part1(input: &Vec<u64>) -> u64 {
-    let result = find_part1(input.into()).expect("find_part1 failed");
-    result[0] * result[2]
-}
+// #[aoc(day10, part1)]
+// pub fn part1(input: &Vec<u64>) -> u64 {
+//     let result = find_part1(input.into()).expect("find_part1 failed");
+//     result[0] * result[2]
+// }
 
-#[aoc(day10, part2)]
-pub fn part2(input: &Vec<u64>) -> u64 {
-    find_part2(input)
-}
+// #[aoc(day10, part2)]
+// pub fn part2(input: &Vec<u64>) -> u64 {
+//     find_part2(input)
+// }
+
+solution!(Day10, 10, Vec<u64>, parse_adapters, part1, part2);
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &str = "16
-10
-15
-5
-1
-11
-7
-19
-6
-12
-4";
-
-    const SAMPLE2: &str = "28
-33
-18
-42
-31
-14
-46
-20
-48
-47
-24
-23
-49
-45
-19
-38
-39
-11
-1
-32
-25
-35
-8
-17
-7
-9
-4
-2
-34
-10
-3";
+    const SAMPLE: &str = include_str!("../examples/day10/sample.txt");
+
+    const SAMPLE2: &str = include_str!("../examples/day10/larger.txt");
 
     #[test]
     fn sample1() {
@@ -139,12 +189,40 @@ mod tests {
     fn sample2_1() {
         let input = input_generator(&SAMPLE);
         let result = part2(&input);
-        assert_eq!(result, 8);
+        assert_eq!(result, BigUint::from(8u32));
     }
     #[test]
     fn sample2_2() {
         let input = input_generator(&SAMPLE2);
         let result = part2(&input);
-        assert_eq!(result, 19208);
+        assert_eq!(result, BigUint::from(19208u32));
+    }
+
+    #[test]
+    fn arrangements() {
+        let chain = AdapterChain::from_joltages(input_generator(SAMPLE), 3).unwrap();
+        assert_eq!(chain.count::<BigUint>(), BigUint::from(8u32));
+        assert_eq!(chain.mandatory(), vec![1, 4, 7, 10, 12, 15, 16, 19]);
+        let arrangements: Vec<Vec<u64>> = chain.arrangements().collect();
+        assert_eq!(arrangements.len(), 8);
+        assert_eq!(
+            arrangements[0],
+            vec![0, 1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19, 22]
+        );
+        assert_eq!(arrangements[7], vec![0, 1, 4, 7, 10, 12, 15, 16, 19, 22]);
+        assert_eq!(chain.nth_arrangement(&BigUint::from(8u32)), None);
+
+        // Gap patterns the old hardcoded match could not handle.
+        let wide = AdapterChain::new(&[1, 2, 3, 4, 5], 4).unwrap();
+        assert_eq!(wide.count::<u128>(), 15);
+        assert!(AdapterChain::new(&[1, 5], 3).is_err());
+        assert!(AdapterChain::new(&[1, 2, 2, 3], 3).is_err());
+        assert!(parse_adapters("1\n2\n2\n").is_err());
+
+        // 1..=300 in steps of one overflows u64 with gaps of up to 3.
+        let adapters: Vec<u64> = (1..=300).collect();
+        let long = AdapterChain::new(&adapters, 3).unwrap();
+        assert_eq!(long.count::<BigUint>().to_string().len(), 80);
+        assert_eq!(part2(&long.joltages), long.count::<BigUint>());
     }
 }
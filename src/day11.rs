@@ -1,9 +1,11 @@
-use anyhow::{anyhow, Result};
+use crate::input::Grid;
+use anyhow::{anyhow, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 use array2d::*;
 use itertools::*;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::fmt;
 use std::sync::Arc;
@@ -12,7 +14,7 @@ use std::{
     iter::FromIterator,
     sync::{Mutex, RwLock},
 };
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum Tile {
     Occupied,
     Empty,
@@ -37,20 +39,27 @@ impl Debug for Tile {
     }
 }
 
+pub fn parse_layout(input: &str) -> Result<Grid<Tile>> {
+    let rows = input
+        .lines()
+        .map(|s| {
+            s.trim()
+                .chars()
+                .map(|b| match b {
+                    'L' => Ok(Tile::Empty),
+                    '#' => Ok(Tile::Occupied),
+                    '.' => Ok(Tile::Floor),
+                    b => Err(anyhow!("Unexpected {:?} in the seat layout", b)),
+                })
+                .collect()
+        })
+        .collect::<Result<Vec<Vec<Tile>>>>()?;
+    Grid::from_rows(&rows).context("Invalid seat layout")
+}
+
 #[aoc_generator(day11)]
 pub fn input_generator(input: &str) -> Array2D<Tile> {
-    let rows = Vec::from_iter(input.lines().map(|s| {
-        s.trim()
-            .chars()
-            .map(|b| match b {
-                'L' => Tile::Empty,
-                '#' => Tile::Occupied,
-                '.' => Tile::Floor,
-                _ => unimplemented!(),
-            })
-            .collect()
-    }));
-    Array2D::from_rows(&rows)
+    parse_layout(input).expect("Could not parse the seat layout").0
 }
 
 #[inline]
@@ -696,20 +705,23 @@ pub fn part2_par2(input: &Array2D<Tile>) -> usize {
         .sum();
 }
 
+solution!(
+    Day11,
+    11,
+    Grid<Tile>,
+    parse_layout,
+    part1,
+    part2,
+    1 "parallel" => part1_par,
+    2 "parallel" => part2_par,
+    2 "parallel2" => part2_par2,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &str = "L.LL.LL.LL
-LLLLLLL.LL
-L.L.L..L..
-LLLL.LL.LL
-L.LL.LL.LL
-L.LLLLL.LL
-..L.L.....
-LLLLLLLLLL
-L.LLLLLL.L
-L.LLLLL.LL";
+    const SAMPLE: &str = include_str!("../examples/day11/sample.txt");
 
     #[test]
     fn sample1() {
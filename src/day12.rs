@@ -1,10 +1,14 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
-use std::iter::FromIterator;
-use std::num::ParseIntError;
+use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, Context, Result};
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Sub};
+use std::path::Path;
 use std::str::FromStr;
+use std::{fs, io};
 
-#[derive(Debug,Copy,Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Action {
     North(usize),
     East(usize),
@@ -16,11 +20,16 @@ pub enum Action {
 }
 
 impl FromStr for Action {
-    type Err = ParseIntError;
+    type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let number = s.chars().skip(1).collect::<String>().parse()?;
-        Ok(match s.chars().nth(0).unwrap() {
+    fn from_str(s: &str) -> Result<Self> {
+        let mut chars = s.chars();
+        let action = chars.next().ok_or_else(|| anyhow!("Empty action"))?;
+        let number = chars
+            .as_str()
+            .parse()
+            .with_context(|| format!("Invalid action {:?}", s))?;
+        Ok(match action {
             'N' => Action::North(number),
             'E' => Action::East(number),
             'S' => Action::South(number),
@@ -28,131 +37,607 @@ impl FromStr for Action {
             'R' => Action::Right(number),
             'L' => Action::Left(number),
             'F' => Action::Forward(number),
-            _ => unimplemented!(),
+            _ => return Err(anyhow!("Unknown action {:?}", s)),
         })
     }
 }
 
+pub fn parse_actions(input: &str) -> Result<Vec<Action>> {
+    input.lines().map(|s| s.trim().parse::<Action>()).collect()
+}
+
 #[aoc_generator(day12)]
 pub fn input_generator(input: &str) -> Vec<Action> {
-    Vec::from_iter(
-        input
-            .lines()
-            .map(|s| s.trim().parse::<Action>().ok().unwrap()),
-    )
+    parse_actions(input).expect("Could not parse the actions")
 }
 
-fn rotate(pos: (i32, i32), degree: i32) -> (i32, i32) {
-    match degree {
-        90|-270 => (-pos.1, pos.0),
-        180|-180 => (-pos.0, -pos.1),
-        270|-90 => (pos.1, -pos.0),
-        _ => unimplemented!()
+// Integer coordinates as long as every turn is a quarter turn, floating point
+// once any other angle comes up.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Point {
+    Exact { x: i64, y: i64 }, // East+, North+
+    Approx { x: f64, y: f64 },
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Point::Exact { x, y }
+    }
+
+    pub fn x(&self) -> f64 {
+        match *self {
+            Point::Exact { x, .. } => x as f64,
+            Point::Approx { x, .. } => x,
+        }
+    }
+
+    pub fn y(&self) -> f64 {
+        match *self {
+            Point::Exact { y, .. } => y as f64,
+            Point::Approx { y, .. } => y,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Point::Exact { .. })
+    }
+
+    fn zip(self, other: Point, exact: fn(i64, i64) -> i64, approx: fn(f64, f64) -> f64) -> Point {
+        match (self, other) {
+            (Point::Exact { x, y }, Point::Exact { x: x2, y: y2 }) => {
+                Point::new(exact(x, x2), exact(y, y2))
+            }
+            _ => Point::Approx {
+                x: approx(self.x(), other.x()),
+                y: approx(self.y(), other.y()),
+            },
+        }
+    }
+
+    pub fn min(self, other: Point) -> Point {
+        self.zip(other, i64::min, f64::min)
+    }
+
+    pub fn max(self, other: Point) -> Point {
+        self.zip(other, i64::max, f64::max)
+    }
+
+    pub fn manhattan(&self) -> f64 {
+        self.x().abs() + self.y().abs()
+    }
+
+    pub fn exact_manhattan(&self) -> Option<i64> {
+        match *self {
+            Point::Exact { x, y } => Some(x.abs() + y.abs()),
+            Point::Approx { .. } => None,
+        }
+    }
+
+    // Rotates counter-clockwise. Multiples of 90 are swaps and sign flips, so
+    // exact points stay exact; anything else goes through sin/cos. A result
+    // back on integer coordinates, like a heading after L45 and L45, is exact
+    // again.
+    pub fn rotate(&self, degree: i32) -> Point {
+        match (*self, degree.rem_euclid(360)) {
+            (_, 0) => *self,
+            (Point::Exact { x, y }, 90) => Point::new(-y, x),
+            (Point::Exact { x, y }, 180) => Point::new(-x, -y),
+            (Point::Exact { x, y }, 270) => Point::new(y, -x),
+            (_, degree) => {
+                let (sin, cos) = (degree as f64).to_radians().sin_cos();
+                Point::Approx {
+                    x: self.x() * cos - self.y() * sin,
+                    y: self.x() * sin + self.y() * cos,
+                }
+                .snap()
+            }
+        }
+    }
+
+    fn snap(self) -> Point {
+        const EPSILON: f64 = 1e-9;
+        match self {
+            Point::Approx { x, y }
+                if (x - x.round()).abs() < EPSILON && (y - y.round()).abs() < EPSILON =>
+            {
+                Point::new(x.round() as i64, y.round() as i64)
+            }
+            _ => self,
+        }
     }
 }
-#[derive(Debug, Clone)]
-struct Ship {
-    direction: usize, // CW, 0 = East
-    pub x: i32, // East+
-    pub y: i32, // North+
-    waypoint_x: i32, // East+
-    waypoint_y: i32, // North+
+
+impl Default for Point {
+    fn default() -> Self {
+        Point::new(0, 0)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        self.zip(rhs, |a, b| a + b, |a, b| a + b)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        self.zip(rhs, |a, b| a - b, |a, b| a - b)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: i64) -> Point {
+        match self {
+            Point::Exact { x, y } => Point::new(x * rhs, y * rhs),
+            Point::Approx { x, y } => Point::Approx {
+                x: x * rhs as f64,
+                y: y * rhs as f64,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ship {
+    pub position: Point,
+    pub heading: Point,  // unit vector, starts East
+    pub waypoint: Point, // relative to the ship
 }
 
 impl Ship {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Ship {
-            direction: 0,
-            x: 0,
-            y: 0,
-            waypoint_x: 10,
-            waypoint_y: 1
-        }
-    }
-
-    fn process_action(&mut self, action: &Action) {
-        match *action {
-            Action::North(distance) => {self.y += distance as i32}
-            Action::East(distance) => {self.x += distance as i32}
-            Action::South(distance) => {self.y -= distance as i32}
-            Action::West(distance) => {self.x -= distance as i32}
-            Action::Right(angle) => {self.direction = (self.direction + angle) % 360}
-            Action::Left(angle) => {self.direction = (360 + self.direction - angle) % 360}
-            Action::Forward(distance) => {
-                match self.direction {
-                    0 => {self.x += distance as i32}
-                    90 => {self.y -= distance as i32}
-                    180 => {self.x -= distance as i32}
-                    270 => {self.y += distance as i32}
-                    _ => unimplemented!(),
-                }
-            }
+            position: Point::new(0, 0),
+            heading: Point::new(1, 0),
+            waypoint: Point::new(10, 1),
         }
     }
+}
 
-    fn process_action_correctly(&mut self, action: &Action) {
-        match *action {
-            Action::North(distance) => {self.waypoint_y += distance as i32}
-            Action::East(distance) => {self.waypoint_x += distance as i32}
-            Action::South(distance) => {self.waypoint_y -= distance as i32}
-            Action::West(distance) => {self.waypoint_x -= distance as i32}
+impl Default for Ship {
+    fn default() -> Self {
+        Ship::new()
+    }
+}
 
-            Action::Right(angle) => {
-                let pos = rotate((self.waypoint_x, self.waypoint_y), -(angle as i32));
-                self.waypoint_x = pos.0;
-                self.waypoint_y = pos.1;
-            }
-            Action::Left(angle) => {
-                let pos = rotate((self.waypoint_x, self.waypoint_y), angle as i32);
-                self.waypoint_x = pos.0;
-                self.waypoint_y = pos.1;
-            }
+fn compass(action: &Action) -> Option<Point> {
+    match *action {
+        Action::North(distance) => Some(Point::new(0, distance as i64)),
+        Action::East(distance) => Some(Point::new(distance as i64, 0)),
+        Action::South(distance) => Some(Point::new(0, -(distance as i64))),
+        Action::West(distance) => Some(Point::new(-(distance as i64), 0)),
+        _ => None,
+    }
+}
 
-            Action::Forward(distance) => {
-                self.x = self.x + self.waypoint_x * distance as i32;
-                self.y = self.y + self.waypoint_y * distance as i32;
-            }
+fn turn(action: &Action) -> Option<i32> {
+    match *action {
+        Action::Left(angle) => Some(angle as i32),
+        Action::Right(angle) => Some(-(angle as i32)),
+        _ => None,
+    }
+}
+
+pub trait MovementModel {
+    fn apply(&self, ship: &mut Ship, action: &Action);
+    // The vector F moves the ship along, per unit of distance.
+    fn pointer(&self, ship: &Ship) -> Point;
+}
+
+// Part 1: N/E/S/W move the ship, L/R turn its heading.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Direct;
+
+impl MovementModel for Direct {
+    fn apply(&self, ship: &mut Ship, action: &Action) {
+        if let Some(offset) = compass(action) {
+            ship.position = ship.position + offset;
+        } else if let Some(angle) = turn(action) {
+            ship.heading = ship.heading.rotate(angle);
+        } else if let Action::Forward(distance) = *action {
+            ship.position = ship.position + ship.heading * distance as i64;
         }
     }
+
+    fn pointer(&self, ship: &Ship) -> Point {
+        ship.heading
+    }
 }
 
-#[aoc(day12, part1)]
-pub fn part1(input: &[Action]) -> i32 {
-    let mut ship = Ship::new();
-    for action in input {
-        ship.process_action(&action);
+// Part 2: N/E/S/W move the waypoint, L/R rotate it around the ship.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Waypoint;
+
+impl MovementModel for Waypoint {
+    fn apply(&self, ship: &mut Ship, action: &Action) {
+        if let Some(offset) = compass(action) {
+            ship.waypoint = ship.waypoint + offset;
+        } else if let Some(angle) = turn(action) {
+            ship.waypoint = ship.waypoint.rotate(angle);
+        } else if let Action::Forward(distance) = *action {
+            ship.position = ship.position + ship.waypoint * distance as i64;
+        }
+    }
+
+    fn pointer(&self, ship: &Ship) -> Point {
+        ship.waypoint
     }
-    return ship.x.abs() + ship.y.abs()
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Step {
+    pub action: Action,
+    pub position: Point,
+    pub heading: Point,
+    pub waypoint: Point,
+}
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
 
-#[aoc(day12, part2)]
-pub fn part2(input: &[Action]) -> i32 {
-    let mut ship = Ship::new();
-    for action in input {
-        ship.process_action_correctly(&action);
+impl BoundingBox {
+    pub fn width(&self) -> f64 {
+        self.max.x() - self.min.x()
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y() - self.min.y()
+    }
+}
+
+fn compare_manhattan(a: Point, b: Point) -> Ordering {
+    match (a.exact_manhattan(), b.exact_manhattan()) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.manhattan().total_cmp(&b.manhattan()),
     }
-    return ship.x.abs() + ship.y.abs()
 }
 
+#[derive(Debug, Clone)]
+pub struct Navigator<M: MovementModel> {
+    model: M,
+    start: Ship,
+    ship: Ship,
+    trajectory: Vec<Step>,
+}
+
+impl<M: MovementModel> Navigator<M> {
+    pub fn new(model: M) -> Self {
+        Navigator::with_ship(model, Ship::new())
+    }
+
+    pub fn with_ship(model: M, ship: Ship) -> Self {
+        Navigator {
+            model,
+            start: ship,
+            ship,
+            trajectory: Vec::new(),
+        }
+    }
+
+    pub fn process_action(&mut self, action: &Action) {
+        self.model.apply(&mut self.ship, action);
+        self.trajectory.push(Step {
+            action: *action,
+            position: self.ship.position,
+            heading: self.ship.heading,
+            waypoint: self.ship.waypoint,
+        });
+    }
+
+    pub fn process_all<'a>(&mut self, actions: impl IntoIterator<Item = &'a Action>) {
+        for action in actions {
+            self.process_action(action);
+        }
+    }
+
+    pub fn start(&self) -> &Ship {
+        &self.start
+    }
+
+    pub fn ship(&self) -> &Ship {
+        &self.ship
+    }
+
+    pub fn trajectory(&self) -> &[Step] {
+        &self.trajectory
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Point> + '_ {
+        std::iter::once(self.start.position).chain(self.trajectory.iter().map(|s| s.position))
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        let start = self.start.position;
+        self.positions().fold(
+            BoundingBox {
+                min: start,
+                max: start,
+            },
+            |bb, p| BoundingBox {
+                min: bb.min.min(p),
+                max: bb.max.max(p),
+            },
+        )
+    }
+
+    // Farthest position from the start, by Manhattan distance.
+    pub fn farthest_point(&self) -> Point {
+        let start = self.start.position;
+        self.positions()
+            .max_by(|a, b| compare_manhattan(*a - start, *b - start))
+            .unwrap()
+    }
+
+    // Manhattan distance from the start.
+    pub fn distance(&self) -> f64 {
+        (self.ship.position - self.start.position).manhattan()
+    }
+
+    // The same, while every turn so far was a quarter turn.
+    pub fn exact_distance(&self) -> Option<i64> {
+        (self.ship.position - self.start.position).exact_manhattan()
+    }
+}
+
+impl<M: MovementModel> Navigator<M> {
+    // The pointer (waypoint or heading) at every F, starting at the ship's
+    // position before the move.
+    pub fn forward_arrows(&self) -> Vec<(Point, Point)> {
+        let mut ship = self.start;
+        let mut arrows = Vec::new();
+        for step in self.trajectory.iter() {
+            if let Action::Forward(_) = step.action {
+                arrows.push((ship.position, self.model.pointer(&ship)));
+            }
+            ship = Ship {
+                position: step.position,
+                heading: step.heading,
+                waypoint: step.waypoint,
+            };
+        }
+        arrows
+    }
+
+    pub fn to_svg(&self) -> String {
+        let bb = self.bounding_box();
+        let size = bb.width().max(bb.height()).max(1.0);
+        let pad = size / 20.0;
+        let stroke = size / 400.0;
+        // SVG's y axis points south.
+        let sx = |p: Point| p.x();
+        let sy = |p: Point| 0.0 - p.y();
+        let start = self.start.position;
+        let end = self.ship.position;
+
+        let mut svg = String::new();
+        svg.push_str(&format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+            bb.min.x() - pad,
+            -bb.max.y() - pad,
+            bb.width() + 2.0 * pad,
+            bb.height() + 3.0 * pad
+        ));
+        svg.push_str(
+            "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+             markerWidth=\"4\" markerHeight=\"4\" orient=\"auto\">\
+             <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"red\"/></marker></defs>\n",
+        );
+        let points: Vec<String> = self
+            .positions()
+            .map(|p| format!("{},{}", sx(p), sy(p)))
+            .collect();
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+            points.join(" "),
+            stroke
+        ));
+        for (from, pointer) in self.forward_arrows() {
+            let to = from + pointer;
+            svg.push_str(&format!(
+                "<line class=\"waypoint\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" \
+                 stroke=\"red\" stroke-width=\"{}\" marker-end=\"url(#arrow)\"/>\n",
+                sx(from),
+                sy(from),
+                sx(to),
+                sy(to),
+                stroke
+            ));
+        }
+        for (label, p, color) in [("start", start, "green"), ("end", end, "blue")].iter() {
+            svg.push_str(&format!(
+                "<circle cx=\"{x}\" cy=\"{y}\" r=\"{r}\" fill=\"{c}\"/>\n\
+                 <text x=\"{x}\" y=\"{y}\" dx=\"{r}\" font-size=\"{f}\" fill=\"{c}\">{l}</text>\n",
+                x = sx(*p),
+                y = sy(*p),
+                r = stroke * 4.0,
+                f = pad / 2.0,
+                c = color,
+                l = label
+            ));
+        }
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\">Manhattan distance: {}</text>\n",
+            bb.min.x(),
+            -bb.min.y() + 1.5 * pad,
+            pad / 2.0,
+            self.distance().round()
+        ));
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn write_svg<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+
+    pub fn to_ascii(&self, columns: usize, rows: usize) -> String {
+        assert!(columns > 1 && rows > 1, "Canvas needs at least 2x2 cells");
+        let bb = self.bounding_box();
+        let cell = |p: Point| -> (i64, i64) {
+            let x = if bb.width() > 0.0 {
+                (p.x() - bb.min.x()) / bb.width() * (columns - 1) as f64
+            } else {
+                0.0
+            };
+            let y = if bb.height() > 0.0 {
+                (bb.max.y() - p.y()) / bb.height() * (rows - 1) as f64
+            } else {
+                0.0
+            };
+            (x.round() as i64, y.round() as i64)
+        };
+        let mut canvas = vec![vec![' '; columns]; rows];
+        let positions: Vec<Point> = self.positions().collect();
+        for segment in positions.windows(2) {
+            let (mut x0, mut y0) = cell(segment[0]);
+            let (x1, y1) = cell(segment[1]);
+            // Bresenham
+            let dx = (x1 - x0).abs();
+            let dy = -(y1 - y0).abs();
+            let step_x = if x0 < x1 { 1 } else { -1 };
+            let step_y = if y0 < y1 { 1 } else { -1 };
+            let mut err = dx + dy;
+            loop {
+                canvas[y0 as usize][x0 as usize] = '#';
+                if x0 == x1 && y0 == y1 {
+                    break;
+                }
+                let e2 = 2 * err;
+                if e2 >= dy {
+                    err += dy;
+                    x0 += step_x;
+                }
+                if e2 <= dx {
+                    err += dx;
+                    y0 += step_y;
+                }
+            }
+        }
+        let (x, y) = cell(self.start.position);
+        canvas[y as usize][x as usize] = 'S';
+        let (x, y) = cell(self.ship.position);
+        canvas[y as usize][x as usize] = 'E';
+
+        let mut out: String = canvas
+            .iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_owned() + "\n")
+            .collect();
+        out.push_str(&format!(
+            "S = ({}, {}), E = ({}, {}), Manhattan distance: {}\n",
+            self.start.position.x().round(),
+            self.start.position.y().round(),
+            self.ship.position.x().round(),
+            self.ship.position.y().round(),
+            self.distance().round()
+        ));
+        out
+    }
+}
+
+// Other turns than quarter turns leave the ship off the integer grid, the
+// distance is rounded then.
+fn rounded_distance<M: MovementModel>(navigator: &Navigator<M>) -> i64 {
+    navigator
+        .exact_distance()
+        .unwrap_or_else(|| navigator.distance().round() as i64)
+}
+
+#[aoc(day12, part1)]
+pub fn part1(input: &[Action]) -> i64 {
+    let mut navigator = Navigator::new(Direct);
+    navigator.process_all(input);
+    rounded_distance(&navigator)
+}
+
+#[aoc(day12, part2)]
+pub fn part2(input: &[Action]) -> i64 {
+    let mut navigator = Navigator::new(Waypoint);
+    navigator.process_all(input);
+    rounded_distance(&navigator)
+}
+
+solution!(Day12, 12, Vec<Action>, parse_actions, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::{*};
 
-    const sample: &str = "F10
-N3
-F7
-R90
-F11";
+    const SAMPLE: &str = include_str!("../examples/day12/sample.txt");
 
     #[test]
     fn sample1() {
-        assert_eq!(part1(&input_generator(&sample)), 25);
+        assert_eq!(part1(&input_generator(&SAMPLE)), 25);
     }
 
     #[test]
     fn sample2() {
-        assert_eq!(part2(&input_generator(&sample)), 286);
+        assert_eq!(part2(&input_generator(&SAMPLE)), 286);
+    }
+
+    #[test]
+    fn arbitrary_angles() {
+        let input = input_generator("R45\nF10\nL135\nF10");
+        let mut navigator = Navigator::new(Direct);
+        navigator.process_all(&input);
+        let end = navigator.ship().position;
+        assert!((end.x() - 50f64.sqrt()).abs() < 1e-9);
+        assert!((end.y() - (10.0 - 50f64.sqrt())).abs() < 1e-9);
+        assert_eq!(navigator.exact_distance(), None);
+        assert_eq!(navigator.trajectory().len(), 4);
+        assert_eq!(navigator.ship().heading, Point::new(0, 1));
+
+        assert_eq!(part1(&input_generator("R45\nF10")), 14);
+        assert_eq!(part2(&input_generator("R45\nF10")), 141);
+        let mut navigator = Navigator::new(Waypoint);
+        navigator.process_all(&input_generator("R45\nR45"));
+        assert_eq!(navigator.ship().waypoint, Point::new(1, -10));
+    }
+
+    #[test]
+    fn trajectory_bounds() {
+        let mut navigator = Navigator::new(Waypoint);
+        navigator.process_all(&input_generator(SAMPLE));
+        let bb = navigator.bounding_box();
+        assert_eq!(bb.min, Point::new(0, -72));
+        assert_eq!(bb.max, Point::new(214, 38));
+        assert_eq!(navigator.farthest_point(), Point::new(214, -72));
+        assert_eq!(navigator.trajectory()[3].waypoint, Point::new(4, -10));
+
+        let start = Ship {
+            position: Point::new(-5, 7),
+            ..Ship::new()
+        };
+        let mut navigator = Navigator::with_ship(Waypoint, start);
+        navigator.process_all(&input_generator(SAMPLE));
+        assert_eq!(navigator.exact_distance(), Some(286));
+        assert_eq!(navigator.farthest_point(), Point::new(209, -65));
+    }
+
+    #[test]
+    fn plot() {
+        let mut navigator = Navigator::new(Waypoint);
+        navigator.process_all(&input_generator(SAMPLE));
+
+        let svg = navigator.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("class=\"waypoint\"").count(), 3);
+        assert!(svg.contains("Manhattan distance: 286"));
+
+        let ascii = navigator.to_ascii(40, 10);
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert!(lines[..10].iter().all(|l| l.len() <= 40));
+        assert_eq!(lines[..10].iter().filter(|l| l.contains('S')).count(), 1);
+        assert_eq!(lines[..10].iter().filter(|l| l.contains('E')).count(), 1);
+        assert!(lines[10].ends_with("Manhattan distance: 286"));
     }
 }
@@ -1,25 +1,41 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+use serde::{Deserialize, Serialize};
 use std::{cmp::Ordering, fmt::Debug, iter::FromIterator};
 use std::num::ParseIntError;
 use std::str::FromStr;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use num_integer::*;
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Input {
     pub earliest_time: usize,
     pub busses: Vec<Option<usize>>
 }
 
+pub fn parse_notes(input: &str) -> Result<Input> {
+    let mut iter = input.lines();
+    let (earliest_time, busses) = match (iter.next(), iter.next()) {
+        (Some(time), Some(busses)) => (time, busses),
+        _ => return Err(anyhow!("Expected a time and a list of busses")),
+    };
+    Ok(Input {
+        earliest_time: earliest_time.trim().parse::<usize>()?,
+        busses: busses
+            .trim()
+            .split(',')
+            .map(|x| match x {
+                "x" => Ok(None),
+                x => x.parse::<usize>().map(Some),
+            })
+            .collect::<Result<_, _>>()?,
+    })
+}
+
 #[aoc_generator(day13)]
 pub fn input_generator(input: &str) -> Input {
-    let mut iter = input.split("\n");
-    Input {
-        earliest_time: iter.nth(0).unwrap().parse::<usize>().unwrap(),
-        busses: iter.nth(0).unwrap().trim().split(",").map(|x| x.parse::<usize>().ok()).collect()
-    }
+    parse_notes(input).expect("Could not parse the notes")
 }
 
 
@@ -66,16 +82,15 @@ pub fn part2_constructive(input: &Input) -> i64 {
     acc.0.rem_euclid(acc.1)
 }
 
+solution!(Day13, 13, Input, parse_notes, part1, part2, 2 "constructive" => part2_constructive);
+
 #[cfg(test)]
 mod tests {
     use super::{*};
 
-    const SAMPLE: &str = "939
-    7,13,x,x,59,x,31,19";
-    const SAMPLE2: &str = "3123
-    17,x,13,19";
-    const SAMPLE3: &str = "3123
-    67,7,59,61";
+    const SAMPLE: &str = include_str!("../examples/day13/sample.txt");
+    const SAMPLE2: &str = include_str!("../examples/day13/sample2.txt");
+    const SAMPLE3: &str = include_str!("../examples/day13/sample3.txt");
 
 
     #[test]
@@ -1,7 +1,9 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::str::FromStr;
 use std::{
     collections::HashMap,
@@ -11,7 +13,10 @@ use std::{
 };
 
 const LOCAL_MASK: u64 = (1u64 << 36) - 1;
-#[derive(Debug, Clone, Copy)]
+// Serialized as it is written in the program, so a parsed mask goes through
+// the same checks when read back.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub struct Mask {
     mask: u64,
     value: u64,
@@ -20,20 +25,41 @@ impl FromStr for Mask {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        assert_eq!(s.len(), 36);
+        if s.len() != 36 || !s.bytes().all(|b| matches!(b, b'X' | b'0' | b'1')) {
+            return Err(anyhow!("Invalid mask {:?}", s));
+        }
 
-        return Ok(Mask {
+        Ok(Mask {
             mask: s.bytes().enumerate().fold(0, |acc, x| match x.1 {
                 b'X' => acc,
-                b'0' | b'1' => acc | 1 << (35 - x.0),
-                _ => unimplemented!(),
+                _ => acc | 1 << (35 - x.0),
             }),
             value: s.bytes().enumerate().fold(0, |acc, x| match x.1 {
-                b'X' | b'0' => acc,
                 b'1' => acc | 1 << (35 - x.0),
-                _ => unimplemented!(),
+                _ => acc,
             }),
-        });
+        })
+    }
+}
+
+impl TryFrom<String> for Mask {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Mask> for String {
+    fn from(mask: Mask) -> Self {
+        (0..36)
+            .rev()
+            .map(|bit| match (mask.mask >> bit & 1, mask.value >> bit & 1) {
+                (0, _) => 'X',
+                (_, 0) => '0',
+                _ => '1',
+            })
+            .collect()
     }
 }
 
@@ -77,43 +103,38 @@ impl Mask {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Input {
     Mask(Mask),
     Mem(u64, u64),
 }
 
-#[aoc_generator(day14)]
-pub fn input_generator(input: &str) -> Vec<Input> {
+pub fn parse_program(input: &str) -> Result<Vec<Input>> {
     input
         .lines()
         .map(|line| {
             let line = line.trim();
-            if line.starts_with("mask") {
-                return Input::Mask(
-                    line.get(7..line.len())
-                        .unwrap()
-                        .parse::<Mask>()
-                        .expect("Could not parse Mask"),
-                );
-            } else if line.starts_with("mem") {
-                let index_end = line.find(']').expect("No closing bracket found");
-                return Input::Mem(
-                    line.get(4..index_end)
-                        .unwrap()
-                        .parse::<u64>()
-                        .expect("Could not parse memory index"),
-                    line.get((index_end + 4)..line.len())
-                        .unwrap()
-                        .parse::<u64>()
-                        .expect("Could not parse value."),
-                );
+            if let Some(mask) = line.strip_prefix("mask = ") {
+                Ok(Input::Mask(mask.parse::<Mask>()?))
+            } else if let Some(rest) = line.strip_prefix("mem[") {
+                let (index, value) = rest
+                    .split_once("] = ")
+                    .ok_or_else(|| anyhow!("Invalid write {:?}", line))?;
+                Ok(Input::Mem(
+                    index.parse::<u64>().context("Invalid memory index")?,
+                    value.parse::<u64>().context("Invalid value")?,
+                ))
             } else {
-                unimplemented!()
+                Err(anyhow!("Unknown instruction {:?}", line))
             }
         })
         .collect()
 }
+
+#[aoc_generator(day14)]
+pub fn input_generator(input: &str) -> Vec<Input> {
+    parse_program(input).expect("Could not parse the program")
+}
 #[derive(Debug, Clone, Copy)]
 pub struct MemoryValue(u64);
 impl From<u64> for MemoryValue {
@@ -246,20 +267,14 @@ pub fn part2_alt(input: &Vec<Input>) -> u64 {
     memory.iter().map(|x| x.1).sum::<u64>()
 }
 
+solution!(Day14, 14, Vec<Input>, parse_program, part1, part2, 2 "alt" => part2_alt);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &str = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
-    mem[8] = 11
-    mem[7] = 101
-    mem[8] = 0";
-    const SAMPLE2: &str = "mask = 000000000000000000000000000000X1001X
-    mem[42] = 100
-    mask = 00000000000000000000000000000000X0XX
-    mem[26] = 1";
-    const SAMPLE3: &str = "3123
-    67,7,59,61";
+    const SAMPLE: &str = include_str!("../examples/day14/sample.txt");
+    const SAMPLE2: &str = include_str!("../examples/day14/floating.txt");
 
     #[test]
     fn sample1() {
@@ -275,4 +290,14 @@ mod tests {
     fn sample2_alt() {
         assert_eq!(part2_alt(&input_generator(&SAMPLE2)), 208);
     }
+
+    #[test]
+    fn mask_json() {
+        let mask = "XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X";
+        let json = serde_json::to_string(&mask.parse::<Mask>().unwrap()).unwrap();
+        assert_eq!(json, format!("{:?}", mask));
+        assert!(serde_json::from_str::<Mask>(&json).is_ok());
+        assert!(serde_json::from_str::<Mask>("\"XX1\"").is_err());
+        assert!(serde_json::from_str::<Mask>(r#"{"mask":1,"value":2}"#).is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 use itertools::Itertools;
@@ -11,15 +11,21 @@ use std::{
 };
 
 
-#[aoc_generator(day15)]
-pub fn input_generator(input: &str) -> Vec<u32> {
+pub fn parse_numbers(input: &str) -> Result<Vec<u32>> {
     input
         .split(",")
         .map(|num| {
-            num.parse::<u32>().expect("Could not parse number")
+            num.trim()
+                .parse::<u32>()
+                .with_context(|| format!("Invalid number {:?}", num))
         })
         .collect()
 }
+
+#[aoc_generator(day15)]
+pub fn input_generator(input: &str) -> Vec<u32> {
+    parse_numbers(input).expect("Could not parse number")
+}
 const BOUNDRY: usize = 3_000_000;
 pub struct SequenceGenerator{
     state: HashMap<u32, usize>,
@@ -90,30 +96,16 @@ pub fn part2(input: &[u32]) -> u32 {
 }
 
 
+solution!(Day15, 15, Vec<u32>, parse_numbers, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE1: &str = "0,3,6";
-    const SAMPLE2: &str = "1,3,2";
-    const SAMPLE3: &str = "2,1,3";
+    const SAMPLE1: &str = include_str!("../examples/day15/sample.txt");
 
     #[test]
     fn sample1() {
         assert_eq!(part1(&input_generator(&SAMPLE1)), 436);
     }
-
-    #[test]
-    fn sample2() {
-        assert_eq!(part2(&input_generator(&SAMPLE1)), 175594);
-    }
-
-    #[test]
-    fn sample2_2() {
-        assert_eq!(part2(&input_generator(&SAMPLE2)), 2578);
-    }
-    #[test]
-    fn sample2_4() {
-        assert_eq!(part2(&input_generator(&SAMPLE3)), 3544142);
-    }
 }
@@ -1,55 +1,315 @@
-use std::{collections::HashMap, ops::RangeInclusive};
+use std::{convert::TryFrom, iter::once, iter::FromIterator, ops::RangeInclusive};
 
-use anyhow::Result;
+use crate::input;
+use crate::solution::Explanation;
+use anyhow::{anyhow, bail, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+// Sorted, non-overlapping and non-adjacent ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "Vec<RangeInclusive<u32>>", from = "Vec<RangeInclusive<u32>>")]
+pub struct IntervalSet {
+    ranges: Vec<RangeInclusive<u32>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    fn normalize(mut ranges: Vec<RangeInclusive<u32>>) -> Self {
+        ranges.retain(|range| !range.is_empty());
+        ranges.sort_by_key(|range| *range.start());
+        let mut merged: Vec<RangeInclusive<u32>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+        IntervalSet { ranges: merged }
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<u32>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        *self = Self::normalize(ranges);
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        let index = self.ranges.partition_point(|range| *range.start() <= value);
+        index > 0 && value <= *self.ranges[index - 1].end()
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        Self::normalize(
+            self.ranges
+                .iter()
+                .chain(other.ranges.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                ranges.push(start..=end);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn ranges(&self) -> &[RangeInclusive<u32>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn max(&self) -> Option<u32> {
+        self.ranges.last().map(|range| *range.end())
+    }
+}
+
+impl FromIterator<RangeInclusive<u32>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<u32>>>(iter: I) -> Self {
+        Self::normalize(iter.into_iter().collect())
+    }
+}
+
+impl From<Vec<RangeInclusive<u32>>> for IntervalSet {
+    fn from(ranges: Vec<RangeInclusive<u32>>) -> Self {
+        Self::normalize(ranges)
+    }
+}
+
+impl From<IntervalSet> for Vec<RangeInclusive<u32>> {
+    fn from(set: IntervalSet) -> Self {
+        set.ranges
+    }
+}
+
+pub const MAX_FIELDS: usize = 64;
+
+// Serialized as its fields, the lookup tables are rebuilt on deserializing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(
+    into = "Vec<(String, IntervalSet)>",
+    try_from = "Vec<(String, IntervalSet)>"
+)]
+pub struct Rules {
+    pub names: Vec<String>,
+    pub sets: Vec<IntervalSet>,
+    any: IntervalSet,
+    // Bit i of masks[j] is set if field i accepts the values from bounds[j]
+    // up to bounds[j + 1].
+    bounds: Vec<u32>,
+    masks: Vec<u64>,
+}
+
+impl Rules {
+    pub fn new(fields: Vec<(String, IntervalSet)>) -> Result<Rules> {
+        if fields.len() > MAX_FIELDS {
+            bail!("At most {} fields are supported", MAX_FIELDS);
+        }
+        let (names, sets): (Vec<String>, Vec<IntervalSet>) = fields.into_iter().unzip();
+        let any = sets
+            .iter()
+            .fold(IntervalSet::new(), |acc, set| acc.union(set));
+        // The fields accepting a value only change where a range starts or
+        // ends, so one mask per such bound covers every value.
+        let mut bounds: Vec<u32> = sets
+            .iter()
+            .flat_map(|set| set.ranges())
+            .flat_map(|range| once(*range.start()).chain(range.end().checked_add(1)))
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+        let masks = bounds
+            .iter()
+            .map(|bound| {
+                sets.iter()
+                    .enumerate()
+                    .filter(|(_, set)| set.contains(*bound))
+                    .fold(0, |mask, (i, _)| mask | 1 << i)
+            })
+            .collect();
+        Ok(Rules {
+            names,
+            sets,
+            any,
+            bounds,
+            masks,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn all_fields(&self) -> u64 {
+        if self.len() == MAX_FIELDS {
+            u64::MAX
+        } else {
+            (1 << self.len()) - 1
+        }
+    }
+
+    #[inline]
+    pub fn mask(&self, value: u32) -> u64 {
+        match self.bounds.partition_point(|bound| *bound <= value) {
+            0 => 0,
+            i => self.masks[i - 1],
+        }
+    }
+
+    #[inline]
+    pub fn is_valid(&self, value: u32) -> bool {
+        self.any.contains(value)
+    }
+}
+
+impl TryFrom<Vec<(String, IntervalSet)>> for Rules {
+    type Error = anyhow::Error;
+
+    fn try_from(fields: Vec<(String, IntervalSet)>) -> Result<Self> {
+        Rules::new(fields)
+    }
+}
+
+impl From<Rules> for Vec<(String, IntervalSet)> {
+    fn from(rules: Rules) -> Self {
+        rules.names.into_iter().zip(rules.sets).collect()
+    }
+}
+
+// Deserialized through the same checks as the puzzle notes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "InputFields")]
 pub struct Input {
-    pub validation: HashMap<String, Vec<RangeInclusive<u32>>>,
+    pub rules: Rules,
     pub my_ticket: Vec<u32>,
     pub other_tickets: Vec<Vec<u32>>,
 }
 
-#[aoc_generator(day16)]
-pub fn input_generator(input: &str) -> Input {
-    let parts: Vec<&str> = input.split("\n\n").collect();
-    let mut validation: HashMap<String, Vec<RangeInclusive<u32>>> = HashMap::new();
-    let mut my_ticket: Vec<u32> = Vec::new();
-    let mut other_tickets: Vec<Vec<u32>> = Vec::new();
-    for line in parts[0].lines() {
-        let split_pos = line.find(":").unwrap();
-        let mut ranges = Vec::new();
-        for range in line[split_pos + 1..line.len()].split(" or ") {
-            let split_pos = range.find("-").unwrap();
-            ranges.push(RangeInclusive::new(
-                range[0..split_pos].trim().parse::<u32>().unwrap(),
-                range[split_pos + 1..range.len()]
-                    .trim()
-                    .parse::<u32>()
-                    .unwrap(),
-            ));
+#[derive(Deserialize)]
+struct InputFields {
+    rules: Rules,
+    my_ticket: Vec<u32>,
+    other_tickets: Vec<Vec<u32>>,
+}
+
+impl Input {
+    // Every nearby ticket has as many values as yours, and the valid ones
+    // leave a single column for each field.
+    pub fn new(rules: Rules, my_ticket: Vec<u32>, other_tickets: Vec<Vec<u32>>) -> Result<Self> {
+        if let Some(ticket) = other_tickets
+            .iter()
+            .find(|ticket| ticket.len() != my_ticket.len())
+        {
+            bail!(
+                "Nearby ticket {:?} does not have {} values",
+                ticket,
+                my_ticket.len()
+            );
         }
-        validation.insert(line[0..split_pos].to_owned(), ranges);
+        let input = Input {
+            rules,
+            my_ticket,
+            other_tickets,
+        };
+        let resolved = field_columns(&input).len();
+        if resolved != input.rules.len() {
+            bail!(
+                "Only {} of {} fields resolve to a single column",
+                resolved,
+                input.rules.len()
+            );
+        }
+        Ok(input)
     }
-    for line in parts[1].lines().skip(1) {
-        my_ticket = line
-            .split(',')
-            .map(|s| s.trim().parse::<u32>().unwrap())
-            .collect();
+}
+
+impl TryFrom<InputFields> for Input {
+    type Error = anyhow::Error;
+
+    fn try_from(fields: InputFields) -> Result<Self> {
+        Input::new(fields.rules, fields.my_ticket, fields.other_tickets)
     }
-    for line in parts[2].lines().skip(1) {
-        let ticket = line
-            .split(',')
-            .map(|s| s.trim().parse::<u32>().unwrap())
-            .collect();
-        other_tickets.push(ticket);
+}
+
+fn parse_ticket(line: &str) -> Result<Vec<u32>> {
+    line.split(',')
+        .map(|s| {
+            s.trim()
+                .parse::<u32>()
+                .with_context(|| format!("Invalid ticket {:?}", line))
+        })
+        .collect()
+}
+
+pub fn parse_notes(input: &str) -> Result<Input> {
+    let parts = input::blocks(input);
+    if parts.len() != 3 {
+        bail!("Expected rules, your ticket and nearby tickets");
     }
-    Input {
-        validation,
-        my_ticket,
-        other_tickets,
+    let mut fields: Vec<(String, IntervalSet)> = Vec::new();
+    for line in parts[0].lines() {
+        let (name, ranges) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid rule {:?}", line))?;
+        let mut set = IntervalSet::new();
+        for range in ranges.split(" or ") {
+            let (start, end) = range
+                .split_once('-')
+                .ok_or_else(|| anyhow!("Invalid range in {:?}", line))?;
+            let parse = |bound: &str| {
+                bound
+                    .trim()
+                    .parse::<u32>()
+                    .with_context(|| format!("Invalid range in {:?}", line))
+            };
+            set.insert(parse(start)?..=parse(end)?);
+        }
+        fields.push((name.trim().to_owned(), set));
     }
+    let my_ticket = match parts[1].lines().nth(1) {
+        Some(line) => parse_ticket(line)?,
+        None => bail!("Missing your ticket"),
+    };
+    let other_tickets = parts[2]
+        .lines()
+        .skip(1)
+        .map(parse_ticket)
+        .collect::<Result<Vec<_>>>()?;
+    Input::new(Rules::new(fields)?, my_ticket, other_tickets)
+}
+
+#[aoc_generator(day16)]
+pub fn input_generator(input: &str) -> Input {
+    parse_notes(input).expect("Could not parse the notes")
 }
 
 #[aoc(day16, part1)]
@@ -57,120 +317,76 @@ pub fn part1(input: &Input) -> u32 {
     input
         .other_tickets
         .iter()
-        .map(|ticket| {
-            ticket
-                .iter()
-                .filter_map(|value| {
-                    if input
-                        .validation
-                        .iter()
-                        .any(|(_, ranges)| ranges.iter().any(|range| range.contains(value)))
-                    {
-                        return None;
-                    }
-                    return Some(value);
-                })
-                .map(|s| *s)
-                .collect::<Vec<u32>>()
-        })
-        .flatten()
+        .flat_map(|ticket| ticket.iter())
+        .filter(|value| !input.rules.is_valid(**value))
         .sum()
 }
-#[inline]
-fn part2_helper(input: &Input) -> Vec<(String, u64)> {
-    let mut classes: HashMap<String, Vec<u64>> = HashMap::new();
-    let other_tickets = input.other_tickets.iter().filter(|ticket| {
-        ticket
-            .iter()
-            .filter_map(|value| {
-                if input
-                    .validation
-                    .iter()
-                    .any(|(_, ranges)| ranges.iter().any(|range| range.contains(value)))
-                {
-                    return None;
-                }
-                return Some(value);
-            })
-            .count()
-            == 0
-    });
-    let len = input.validation.len().clone();
-    let validation = input.validation.clone();
-    for i in 0..len {
-        for (class, ranges) in validation.iter() {
-            if other_tickets
-                .clone()
-                .all(|ticket| ranges.iter().any(|r| r.contains(&ticket[i])))
-            {
-                if let Some(v) = classes.get_mut(class) {
-                    v.push(input.my_ticket[i] as u64);
-                } else {
-                    classes.insert(class.clone(), vec![input.my_ticket[i] as u64]);
-                }
-            }
+
+// Field names and the column each one maps to, in the order they were
+// resolved.
+pub fn field_columns(input: &Input) -> Vec<(String, usize)> {
+    let rules = &input.rules;
+    let mut candidates = vec![rules.all_fields(); input.my_ticket.len()];
+    for ticket in input
+        .other_tickets
+        .iter()
+        .filter(|ticket| ticket.len() == input.my_ticket.len())
+        .filter(|ticket| ticket.iter().all(|value| rules.is_valid(*value)))
+    {
+        for (column, value) in ticket.iter().enumerate() {
+            candidates[column] &= rules.mask(*value);
         }
     }
-    let mut unique_classes: Vec<(String, u64)> = Vec::new();
-    while classes.len() != 0 {
-        {
-            unique_classes.extend(
-                classes
-                    .iter()
-                    .filter(|s| s.1.len() == 1)
-                    .map(|s| (s.0.clone(), s.1[0])),
-            );
-        }
-        {
-            for uniqe_class in unique_classes.iter() {
-                classes.iter_mut().for_each(|x| {
-                    let class = x.1;
 
-                    if let Some(pos) = class.iter().position(|s| *s == uniqe_class.1) {
-                        class.remove(pos);
-                    }
-                });
-            }
-            classes
-                .clone()
-                .iter()
-                .filter(|s| s.1.len() == 0)
-                .for_each(|s| {
-                    classes.remove(s.0);
-                });
-        }
+    let mut columns = Vec::new();
+    while let Some(column) = candidates.iter().position(|c| c.count_ones() == 1) {
+        let field = candidates[column];
+        candidates.iter_mut().for_each(|c| *c &= !field);
+        columns.push((rules.names[field.trailing_zeros() as usize].clone(), column));
     }
-    unique_classes
+    columns
 }
 
+#[inline]
+fn part2_helper(input: &Input) -> Vec<(String, u64)> {
+    field_columns(input)
+        .into_iter()
+        .map(|(name, column)| (name, input.my_ticket[column] as u64))
+        .collect()
+}
 #[aoc(day16, part2)]
 pub fn part2(input: &Input) -> u64 {
-    let my_classes = part2_helper(input);
-    assert_eq!(my_classes.len(), input.validation.len());
-
-    my_classes
+    part2_helper(input)
         .iter()
         .filter(|(class, _)| class.starts_with("departure"))
         .map(|(_, value)| value)
         .product()
 }
 
+fn explain(part: u32, input: &Input) -> Option<Explanation> {
+    if part != 2 {
+        return None;
+    }
+    let mut columns = field_columns(input);
+    columns.sort_by_key(|(_, column)| *column);
+    Some(Explanation::Fields(columns))
+}
+
+solution!(
+    Day16,
+    16,
+    Input,
+    parse_notes,
+    part1,
+    part2,
+    explain = explain
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE1: &str = "class: 1-3 or 5-7
-row: 6-11 or 33-44
-seat: 13-40 or 45-50
-
-your ticket:
-7,1,14
-
-nearby tickets:
-7,3,47
-40,4,50
-55,2,20
-38,6,12";
+    const SAMPLE1: &str = include_str!("../examples/day16/sample.txt");
     const SAMPLE2: &str = "class: 0-1 or 4-19
 row: 0-5 or 8-19
 seat: 0-13 or 16-19
@@ -198,4 +414,50 @@ nearby tickets:
         .iter()
         .all(|s| part2_helper(&input_generator(&SAMPLE2)).contains(s)));
     }
+
+    #[test]
+    fn interval_set() {
+        let a: IntervalSet = vec![5..=7, 1..=3, 4..=4, 10..=12].into_iter().collect();
+        assert_eq!(a.ranges(), &[1..=7, 10..=12]);
+        assert!(a.contains(1) && a.contains(7) && a.contains(11));
+        assert!(!a.contains(0) && !a.contains(8) && !a.contains(13));
+
+        let b: IntervalSet = vec![6..=11, 20..=30].into_iter().collect();
+        assert_eq!(a.union(&b).ranges(), &[1..=12, 20..=30]);
+        assert_eq!(a.intersection(&b).ranges(), &[6..=7, 10..=11]);
+    }
+
+    #[test]
+    fn parsed_rules() {
+        let rules: Rules =
+            serde_json::from_str(r#"[["class", [{"start": 1, "end": 3}]]]"#).unwrap();
+        assert_eq!(rules.mask(2), 1);
+        let fields: Vec<String> = (0..=MAX_FIELDS)
+            .map(|i| format!(r#"["f{}", []]"#, i))
+            .collect();
+        assert!(serde_json::from_str::<Rules>(&format!("[{}]", fields.join(","))).is_err());
+
+        let wide =
+            parse_notes("a: 0-4000000000\nb: 5-10\n\nyour ticket:\n7,7\n\nnearby tickets:\n3,7")
+                .unwrap();
+        assert_eq!(wide.rules.mask(7), 0b11);
+        assert_eq!(wide.rules.mask(4000000000), 0b01);
+        assert_eq!(wide.rules.mask(u32::MAX), 0);
+        assert!(parse_notes(&SAMPLE1.replace("38,6,12", "38,6,12,1")).is_err());
+        assert!(
+            parse_notes("a: 1-5\nb: 1-5\n\nyour ticket:\n1,2\n\nnearby tickets:\n3,4").is_err()
+        );
+
+        let json = serde_json::to_string(&input_generator(SAMPLE2)).unwrap();
+        assert_eq!(
+            part2(&serde_json::from_str::<Input>(&json).unwrap()),
+            part2(&input_generator(SAMPLE2))
+        );
+        let ambiguous = json.replace(
+            r#"[{"start":0,"end":1},{"start":4,"end":19}]"#,
+            r#"[{"start":0,"end":19}]"#,
+        );
+        assert_ne!(ambiguous, json);
+        assert!(serde_json::from_str::<Input>(&ambiguous).is_err());
+    }
 }
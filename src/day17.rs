@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 #[cfg(test)]
@@ -9,8 +9,11 @@ use legion::{
     *,
 };
 use rayon::iter::ParallelIterator;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 #[cfg(test)]
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ops::Add;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -74,8 +77,71 @@ impl Add<PosOffset> for (X, Y, Z, W) {
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct Active;
 
-#[aoc_generator(day17)]
-pub fn input_generator(input: &str) -> World {
+#[derive(Serialize, Deserialize)]
+struct Cube {
+    x: i64,
+    y: i64,
+    z: i64,
+    w: i64,
+    active: bool,
+}
+
+// The generated world, serialized as its cubes.
+pub struct Pocket(pub World);
+
+impl Serialize for Pocket {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut query = <(&X, &Y, &Z, &W, Option<&Active>)>::query();
+        serializer.collect_seq(query.iter(&self.0).map(|(x, y, z, w, active)| Cube {
+            x: x.0,
+            y: y.0,
+            z: z.0,
+            w: w.0,
+            active: active.is_some(),
+        }))
+    }
+}
+
+// Like a parsed slice, the cubes have to fill a rectangle at z = 0 and w = 0
+// starting from the origin, once each.
+impl<'de> Deserialize<'de> for Pocket {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cubes = Vec::<Cube>::deserialize(deserializer)?;
+        let positions: HashSet<(i64, i64)> = cubes.iter().map(|c| (c.x, c.y)).collect();
+        let width = cubes
+            .iter()
+            .map(|c| c.x.saturating_add(1))
+            .max()
+            .unwrap_or(0);
+        let height = cubes
+            .iter()
+            .map(|c| c.y.saturating_add(1))
+            .max()
+            .unwrap_or(0);
+        if cubes
+            .iter()
+            .any(|c| c.x < 0 || c.y < 0 || c.z != 0 || c.w != 0)
+            || positions.len() != cubes.len()
+            || width.checked_mul(height) != Some(positions.len() as i64)
+        {
+            return Err(D::Error::custom(
+                "The cubes do not form a slice starting at the origin",
+            ));
+        }
+        let mut world = World::default();
+        for cube in cubes {
+            let (x, y, z, w) = (X(cube.x), Y(cube.y), Z(cube.z), W(cube.w));
+            if cube.active {
+                world.push((x, y, z, w, Active));
+            } else {
+                world.push((x, y, z, w));
+            }
+        }
+        Ok(Pocket(world))
+    }
+}
+
+pub fn parse_slice(input: &str) -> Result<Pocket> {
     let mut world = World::default();
     let z = 0;
     let mut y = 0;
@@ -86,7 +152,7 @@ pub fn input_generator(input: &str) -> World {
             match line_char {
                 '#' => world.push((X(x), Y(y), Z(z), W(0), Active)),
                 '.' => world.push((X(x), Y(y), Z(z), W(0))),
-                _ => unimplemented!(),
+                c => return Err(anyhow!("Unexpected {:?} in the slice", c)),
             };
             x += 1;
         }
@@ -102,7 +168,12 @@ pub fn input_generator(input: &str) -> World {
     //     }
     // }
 
-    world
+    Ok(Pocket(world))
+}
+
+#[aoc_generator(day17)]
+pub fn input_generator(input: &str) -> Pocket {
+    parse_slice(input).expect("Could not parse the slice")
 }
 
 // fn set_active(commands: &mut CommandBuffer, entity: Entity, active: bool) {
@@ -447,14 +518,14 @@ impl Kernel {
 }
 
 #[aoc(day17, part1)]
-pub fn part1(input: &World) -> usize {
+pub fn part1(input: &Pocket) -> usize {
     let mut merger = Duplicate::default();
     merger.register_copy::<X>();
     merger.register_copy::<Y>();
     merger.register_copy::<Z>();
     merger.register_copy::<Active>();
     let mut world = World::default();
-    world.clone_from(input, &any(), &mut merger);
+    world.clone_from(&input.0, &any(), &mut merger);
     let mut schedule = Schedule::builder()
         .add_system(expand_world_system(WorldState::default()))
         .flush()
@@ -477,7 +548,7 @@ pub fn part1(input: &World) -> usize {
 }
 
 #[aoc(day17, part2)]
-pub fn part2(input: &World) -> usize {
+pub fn part2(input: &Pocket) -> usize {
     let mut merger = Duplicate::default();
     merger.register_copy::<X>();
     merger.register_copy::<Y>();
@@ -485,7 +556,7 @@ pub fn part2(input: &World) -> usize {
     merger.register_copy::<W>();
     merger.register_copy::<Active>();
     let mut world = World::default();
-    world.clone_from(input, &any(), &mut merger);
+    world.clone_from(&input.0, &any(), &mut merger);
     let mut schedule = Schedule::builder()
         .add_system(expand_world_4d_system(WorldState::default()))
         .flush()
@@ -507,26 +578,42 @@ pub fn part2(input: &World) -> usize {
     return query.iter(&world).count();
 }
 
+solution!(Day17, 17, Pocket, parse_slice, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE1: &str = ".#.
-..#
-###";
+    const SAMPLE1: &str = include_str!("../examples/day17/sample.txt");
 
     #[test]
     fn sample1() {
         assert_eq!(part1(&input_generator(&SAMPLE1)), 112);
     }
     #[test]
-    fn sample_kernel() {
-        assert_eq!(Kernel(Part::One).iter().count(), 26);
-        assert_eq!(Kernel(Part::Two).iter().count(), 80);
+    fn pocket_json() {
+        let json = serde_json::to_string(&input_generator(SAMPLE1)).unwrap();
+        let pocket: Pocket = serde_json::from_str(&json).unwrap();
+        assert_eq!(part1(&pocket), 112);
+        let cube = |x: i64, y: i64, z: i64| {
+            format!(r#"{{"x":{},"y":{},"z":{},"w":0,"active":true}}"#, x, y, z)
+        };
+        for cubes in [
+            vec![cube(0, 0, 0), cube(0, 0, 0)],
+            vec![cube(0, 0, 0), cube(2, 0, 0)],
+            vec![cube(0, 0, 1)],
+            vec![cube(i64::MAX, 0, 0)],
+        ]
+        .iter()
+        {
+            let json = format!("[{}]", cubes.join(","));
+            assert!(serde_json::from_str::<Pocket>(&json).is_err(), "{}", json);
+        }
     }
 
     #[test]
-    fn sample2() {
-        assert_eq!(part2(&input_generator(&SAMPLE1)), 848);
+    fn sample_kernel() {
+        assert_eq!(Kernel(Part::One).iter().count(), 26);
+        assert_eq!(Kernel(Part::Two).iter().count(), 80);
     }
 }
@@ -87,14 +87,16 @@ pub fn part2(input: &str) -> i64 {
         .sum()
 }
 
+solution!(Day18, 18, String, |input: &str| Ok(input.to_owned()), part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE1: &str = "2 * 3 + (4 * 5)";
-    const SAMPLE2: &str = "5 + (8 * 3 + 9 + 3 * 4 * 3)";
-    const SAMPLE3: &str = "5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))";
-    const SAMPLE4: &str = "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
+    const SAMPLE1: &str = include_str!("../examples/day18/sample1.txt");
+    const SAMPLE2: &str = include_str!("../examples/day18/sample2.txt");
+    const SAMPLE3: &str = include_str!("../examples/day18/sample3.txt");
+    const SAMPLE4: &str = include_str!("../examples/day18/sample4.txt");
 
     #[test]
     fn sample1() {
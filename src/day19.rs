@@ -1,11 +1,13 @@
-use std::{cell::RefCell, char, str::FromStr};
+use std::{cell::RefCell, char, collections::BTreeMap, str::FromStr};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+use crate::input;
 use itertools::Itertools;
 use nom::{IResult, Parser, bits::complete::take, branch::alt, branch::permutation, character::complete::{alpha0, anychar, char, digit1, one_of, space0, space1}, combinator::{map_res}, multi::{fold_many0, separated_list1}, sequence::{delimited, pair, preceded, separated_pair}};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq)]
 enum Rule {
@@ -21,8 +23,10 @@ impl Rule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Input {
-    rules: Vec<String>,
+    // Rule numbers need not be contiguous.
+    rules: BTreeMap<usize, String>,
     strings: Vec<String>
 }
 
@@ -39,26 +43,35 @@ fn parse_rule_or(input: &str) -> IResult<&str, Rule> {
 }
 
 
+pub fn parse_input(input: &str) -> Result<Input> {
+    let blocks = input::blocks(input);
+    if blocks.len() != 2 {
+        return Err(anyhow!("Expected the rules and the messages, found {} blocks", blocks.len()));
+    }
+    let mut rules = BTreeMap::new();
+    for s in blocks[0].lines() {
+        let line = s.trim();
+        let split_pos = line.find(':').ok_or_else(|| anyhow!("Expected a ':' in {:?}", line))?;
+        let number = (line[0..split_pos]).parse::<usize>()?;
+        if rules.insert(number, line[split_pos+1..line.len()].to_owned()).is_some() {
+            bail!("Rule {} appears twice", number);
+        }
+    }
+
+    Ok(Input { 
+        rules,
+        strings: blocks[1].lines().map(|s| s.trim().to_owned()).collect()
+    })
+}
+
 #[aoc_generator(day19)]
 pub fn input_generator(input: &str) -> Input {
-    let rules = input.split("\n\n").collect::<Vec<&str>>();
-    let mut rule_vec = rules[0].lines()
-        .map(|s| {
-            let line = s.trim();
-            let split_pos = line.find(":").unwrap();
-            ((line[0..split_pos]).parse::<usize>().unwrap(), (line[split_pos+1..line.len()].to_owned()))
-        }).collect::<Vec<(usize, String)>>();
-        rule_vec.sort_by_key(|k| k.0);
-
-    Input { 
-        rules: rule_vec.iter().map(|r| r.1.clone()).collect(),
-        strings: rules[1].lines().map(|s| s.trim().to_owned()).collect()
-    }
+    parse_input(input).expect("Could not parse the rules")
 }
 
-fn build_regex(string: &mut String, index: usize, rules: &Vec<Rule>) {
+fn build_regex(string: &mut String, index: usize, rules: &BTreeMap<usize, Rule>) {
 
-    match &rules[index] {
+    match &rules[&index] {
         Rule::Alphabet(c) => string.push(*c),
         Rule::Or(vec) => {
             if vec.len() > 1 {
@@ -82,7 +95,7 @@ fn build_regex(string: &mut String, index: usize, rules: &Vec<Rule>) {
 
 #[aoc(day19, part1)]
 pub fn part1(input: &Input) -> i64 {
-    let rules = input.rules.iter().map(|rule| alt((parse_rule_or, parse_rule_alphabet))(rule.trim()).expect("Could not parse rule")).map(|(_, r)| r).collect::<Vec<Rule>>();
+    let rules = input.rules.iter().map(|(n, rule)| (*n, alt((parse_rule_or, parse_rule_alphabet))(rule.trim()).expect("Could not parse rule").1)).collect::<BTreeMap<usize, Rule>>();
 
     let mut regex_string = "(?m)^".to_string();
     build_regex(&mut regex_string, 0, &rules);
@@ -101,10 +114,10 @@ pub fn part1(input: &Input) -> i64 {
 #[aoc(day19, part2)]
 pub fn part2(input: &Input) -> u64 {
     let mut strrules = input.rules.clone();
-    strrules[8] = "42 | 42 8".to_owned();
-    strrules[11] = "42 31 | 42 11 31".to_owned();
+    strrules.insert(8, "42 | 42 8".to_owned());
+    strrules.insert(11, "42 31 | 42 11 31".to_owned());
 
-    let rules = strrules.iter().map(|rule| alt((parse_rule_or, parse_rule_alphabet))(rule.trim()).expect("Could not parse rule")).map(|(_, r)| r).collect::<Vec<Rule>>();
+    let rules = strrules.iter().map(|(n, rule)| (*n, alt((parse_rule_or, parse_rule_alphabet))(rule.trim()).expect("Could not parse rule").1)).collect::<BTreeMap<usize, Rule>>();
 
     let mut r42 = "(".to_owned();
     build_regex(&mut r42, 42, &rules);
@@ -135,26 +148,25 @@ pub fn part2(input: &Input) -> u64 {
     valid
 }
 
+solution!(Day19, 19, Input, parse_input, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE1: &str = r#"0: 4 1 5
-1: 2 3 | 3 2
-2: 4 4 | 5 5
-3: 4 5 | 5 4
-4: "a"
-5: "b"
-
-ababbb
-bababa
-abbbab
-aaabbb
-aaaabbb"#;
+    const SAMPLE1: &str = include_str!("../examples/day19/sample.txt");
+    const LOOPS: &str = include_str!("../examples/day19/loops.txt");
 
     #[test]
     fn sample1() {
         assert_eq!(part1(&input_generator(&SAMPLE1)), 2);
+        assert_eq!(part1(&input_generator(LOOPS)), 3);
+    }
+
+    #[test]
+    fn loops() {
+        assert_eq!(part2(&input_generator(LOOPS)), 12);
+        assert!(parse_input("0: 1\n1: \"a\"\n1: \"b\"\n\na").is_err());
     }
 
     // #[test]
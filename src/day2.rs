@@ -1,81 +1,299 @@
+use anyhow::{anyhow, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
-use std::iter::FromIterator;
-use std::num::ParseIntError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
 use std::str::FromStr;
 
-#[derive(Debug)]
+// Deserialized through the same checks as a line of the puzzle input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "EntryFields")]
 pub struct Entry {
     min: usize,
     max: usize,
-    char: String,
+    letter: char,
     password: String,
 }
 
+#[derive(Deserialize)]
+struct EntryFields {
+    min: usize,
+    max: usize,
+    letter: char,
+    password: String,
+}
+
+impl Entry {
+    pub fn new(min: usize, max: usize, letter: char, password: String) -> Result<Self> {
+        if min == 0 || min > max {
+            return Err(anyhow!("Invalid positions {}-{}", min, max));
+        }
+        Ok(Entry {
+            min,
+            max,
+            letter,
+            password,
+        })
+    }
+}
+
+impl TryFrom<EntryFields> for Entry {
+    type Error = anyhow::Error;
+
+    fn try_from(fields: EntryFields) -> Result<Self> {
+        Entry::new(fields.min, fields.max, fields.letter, fields.password)
+    }
+}
+
 impl FromStr for Entry {
-    type Err = ParseIntError;
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (policy, password) = s
+            .split_once(": ")
+            .ok_or_else(|| anyhow!("Missing ': ' in {:?}", s))?;
+        let (range, letter) = policy
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("Missing letter in {:?}", s))?;
+        let (min, max) = range
+            .split_once('-')
+            .ok_or_else(|| anyhow!("Missing '-' in {:?}", s))?;
+        let min = min.parse::<usize>()?;
+        let max = max.parse::<usize>()?;
+        let mut letters = letter.chars();
+        let letter = match (letters.next(), letters.next()) {
+            (Some(letter), None) => letter,
+            _ => return Err(anyhow!("Expected a single letter in {:?}", s)),
+        };
+        Entry::new(min, max, letter, password.to_owned()).with_context(|| format!("in {:?}", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyFailure {
+    pub policy: String,
+    pub reason: String,
+}
+
+impl fmt::Display for PolicyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.policy, self.reason)
+    }
+}
+
+pub trait PasswordPolicy {
+    fn name(&self) -> String;
+    fn violation(&self, entry: &Entry) -> Option<String>;
+
+    fn check(&self, entry: &Entry) -> Result<(), PolicyFailure> {
+        match self.violation(entry) {
+            None => Ok(()),
+            Some(reason) => Err(PolicyFailure {
+                policy: self.name(),
+                reason,
+            }),
+        }
+    }
+}
+
+// Part 1: the letter occurs between min and max times.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SledRental;
+
+impl PasswordPolicy for SledRental {
+    fn name(&self) -> String {
+        "sled rental".to_owned()
+    }
+
+    fn violation(&self, entry: &Entry) -> Option<String> {
+        let count = entry
+            .password
+            .chars()
+            .filter(|c| *c == entry.letter)
+            .count();
+        if count >= entry.min && count <= entry.max {
+            None
+        } else {
+            Some(format!(
+                "{:?} occurs {} times, expected {}-{}",
+                entry.letter, count, entry.min, entry.max
+            ))
+        }
+    }
+}
+
+// Part 2: the letter is at exactly one of the (1-based) positions min and max.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Toboggan;
+
+impl PasswordPolicy for Toboggan {
+    fn name(&self) -> String {
+        "toboggan".to_owned()
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(|c| c == ' ' || c == ':' || c == '-').collect();
-        return Ok(Entry {
-            min: parts[0].parse::<usize>()?,
-            max: parts[1].parse::<usize>()?,
-            char: parts[2].to_owned(),
-            password: parts[4].to_owned(),
-        });
+    fn violation(&self, entry: &Entry) -> Option<String> {
+        let chars: Vec<char> = entry.password.chars().collect();
+        if entry.max > chars.len() {
+            return Some(format!(
+                "position {} is past the end of a {} character password",
+                entry.max,
+                chars.len()
+            ));
+        }
+        match (
+            chars[entry.min - 1] == entry.letter,
+            chars[entry.max - 1] == entry.letter,
+        ) {
+            (true, false) | (false, true) => None,
+            (true, true) => Some(format!(
+                "{:?} is at both positions {} and {}",
+                entry.letter, entry.min, entry.max
+            )),
+            (false, false) => Some(format!(
+                "{:?} is at neither position {} nor {}",
+                entry.letter, entry.min, entry.max
+            )),
+        }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Matches(pub Regex);
+
+impl PasswordPolicy for Matches {
+    fn name(&self) -> String {
+        format!("matches /{}/", self.0.as_str())
+    }
+
+    fn violation(&self, entry: &Entry) -> Option<String> {
+        if self.0.is_match(&entry.password) {
+            None
+        } else {
+            Some(format!("{:?} does not match", entry.password))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MustContain(pub Vec<char>);
+
+impl PasswordPolicy for MustContain {
+    fn name(&self) -> String {
+        format!("must contain {:?}", self.0.iter().collect::<String>())
+    }
+
+    fn violation(&self, entry: &Entry) -> Option<String> {
+        let missing: String = self
+            .0
+            .iter()
+            .filter(|c| !entry.password.contains(**c))
+            .collect();
+        if missing.is_empty() {
+            None
+        } else {
+            Some(format!("missing {:?}", missing))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ForbiddenSubstrings(pub Vec<String>);
+
+impl PasswordPolicy for ForbiddenSubstrings {
+    fn name(&self) -> String {
+        format!("forbids {:?}", self.0)
+    }
+
+    fn violation(&self, entry: &Entry) -> Option<String> {
+        self.0
+            .iter()
+            .find(|forbidden| entry.password.contains(forbidden.as_str()))
+            .map(|forbidden| format!("contains {:?}", forbidden))
+    }
+}
+
+pub fn check_all(entry: &Entry, policies: &[&dyn PasswordPolicy]) -> Vec<PolicyFailure> {
+    policies
+        .iter()
+        .filter_map(|policy| policy.check(entry).err())
+        .collect()
+}
+
+pub fn parse_entries(input: &str) -> Result<Vec<Entry>> {
+    input.lines().map(|s| s.trim().parse::<Entry>()).collect()
+}
+
 #[aoc_generator(day2)]
 pub fn input_generator(input: &str) -> Vec<Entry> {
-    Vec::from_iter(
-        input
-            .lines()
-            .map(|s| s.trim().parse::<Entry>().ok().unwrap()),
-    )
+    parse_entries(input).expect("Could not parse entry")
 }
 
 #[aoc(day2, part1)]
 pub fn part1(input: &[Entry]) -> usize {
-    input
-        .iter()
-        .filter(|x| {
-            let len = x
-                .password
-                .bytes()
-                .filter(|c| *c == x.char.as_bytes()[0])
-                .count();
-            len >= x.min && len <= x.max
-        })
-        .count()
+    input.iter().filter(|x| SledRental.check(x).is_ok()).count()
 }
 
 #[aoc(day2, part2)]
 pub fn part2(input: &[Entry]) -> usize {
-    input
-        .iter()
-        .filter(|x| {
-            (x.password.as_bytes()[x.min - 1] == x.char.as_bytes()[0])
-                ^ (x.password.as_bytes()[x.max - 1] == x.char.as_bytes()[0])
-        })
-        .count()
+    input.iter().filter(|x| Toboggan.check(x).is_ok()).count()
 }
 
+solution!(Day2, 2, Vec<Entry>, parse_entries, part1, part2);
+
 #[cfg(test)]
 mod tests {
-    use super::{input_generator, part1, part2};
+    use super::*;
 
-    const sample: &str = "1-3 a: abcde
-    1-3 b: cdefg
-    2-9 c: ccccccccc";
+    const SAMPLE: &str = include_str!("../examples/day02/sample.txt");
 
     #[test]
     fn sample1() {
-        assert_eq!(part1(&input_generator(&sample)), 2);
+        assert_eq!(part1(&input_generator(&SAMPLE)), 2);
     }
 
     #[test]
     fn sample2() {
-        assert_eq!(part2(&input_generator(&sample)), 1);
+        assert_eq!(part2(&input_generator(&SAMPLE)), 1);
+    }
+
+    #[test]
+    fn policies() {
+        let input = input_generator(SAMPLE);
+        let failures = check_all(&input[1], &[&SledRental, &Toboggan]);
+        assert_eq!(
+            failures.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+            vec![
+                "sled rental: 'b' occurs 0 times, expected 1-3",
+                "toboggan: 'b' is at neither position 1 nor 3",
+            ]
+        );
+
+        let entry: Entry = "1-9 a: abc".parse().unwrap();
+        assert_eq!(
+            Toboggan.check(&entry).unwrap_err().reason,
+            "position 9 is past the end of a 3 character password"
+        );
+        assert!("0-3 a: abc".parse::<Entry>().is_err());
+        assert!("3-1 a: abc".parse::<Entry>().is_err());
+        assert!("1-3 ab: abc".parse::<Entry>().is_err());
+        assert!(serde_json::from_str::<Entry>(
+            r#"{"min":0,"max":3,"letter":"a","password":"abc"}"#
+        )
+        .is_err());
+
+        let policies: [&dyn PasswordPolicy; 3] = [
+            &Matches(Regex::new("^[a-z]+$").unwrap()),
+            &MustContain(vec!['a', 'x']),
+            &ForbiddenSubstrings(vec!["bc".to_owned()]),
+        ];
+        assert_eq!(
+            check_all(&entry, &policies)
+                .iter()
+                .map(|f| f.reason.as_str())
+                .collect::<Vec<_>>(),
+            vec!["missing \"x\"", "contains \"bc\""]
+        );
     }
 }
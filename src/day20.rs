@@ -1,6 +1,8 @@
 use std::{collections::HashMap, fmt::Display};
 
-use anyhow::Result;
+use crate::input;
+use crate::solution::Explanation;
+use anyhow::{bail, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 use array2d::Array2D;
@@ -12,15 +14,41 @@ use nom::{
     sequence::{pair, preceded, terminated, tuple},
     IResult,
 };
-use shrinkwraprs::Shrinkwrap;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::hash::Hash;
 
 const NESSI: &str = "                  # 
 #    ##    ##    ###
  #  #  #  #  #  #   ";
 
+type Tiles = HashMap<TileId, Vec<(usize, usize)>>;
+type OrientedTiles = HashMap<TileId, (Edges, Vec<(usize, usize)>)>;
+
+#[derive(Serialize, Deserialize)]
 pub struct Input {
-    tiles: HashMap<TileId, Vec<(usize, usize)>>,
+    #[serde(deserialize_with = "deserialize_tiles")]
+    tiles: Tiles,
+}
+
+// The same checks parse_tiles makes: some tiles, each with its cells on a
+// 10x10 grid.
+fn deserialize_tiles<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Tiles, D::Error> {
+    let tiles = Tiles::deserialize(deserializer)?;
+    if tiles.is_empty() {
+        return Err(D::Error::custom("There are no tiles"));
+    }
+    if let Some(id) = tiles
+        .iter()
+        .find(|(_, cells)| cells.iter().any(|&(x, y)| x >= 10 || y >= 10))
+        .map(|(id, _)| id.0)
+    {
+        return Err(D::Error::custom(format!(
+            "Tile {} has cells outside of 10x10",
+            id
+        )));
+    }
+    Ok(tiles)
 }
 fn parse_tile_header(input: &str) -> IResult<&str, TileId> {
     let (input, id) = terminated(
@@ -34,9 +62,7 @@ fn parse_tile_header(input: &str) -> IResult<&str, TileId> {
 }
 fn parse_tile(input: &str) -> IResult<&str, Vec<(usize, usize)>> {
     let (input, rows) = separated_list1(line_ending, many1(one_of("#.")))(input)?;
-    assert_eq!(rows.len(), 10);
     let content = rows.iter().enumerate().fold(vec![], |acc, (r, row)| {
-        assert_eq!(row.len(), 10);
         row.iter().enumerate().fold(acc, |mut acc, (c, field)| {
             if field == &'#' {
                 acc.push((c, r));
@@ -44,27 +70,40 @@ fn parse_tile(input: &str) -> IResult<&str, Vec<(usize, usize)>> {
             acc
         })
     });
+    if rows.len() != 10 || rows.iter().any(|row| row.len() != 10) {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
 
     Ok((input, content))
 }
 
+pub fn parse_tiles(input: &str) -> Result<Input> {
+    let mut tiles = Tiles::new();
+    for block in input::blocks(input) {
+        let header = block.lines().next().unwrap_or_default();
+        let (id, cells) = match tuple((parse_tile_header, parse_tile))(block) {
+            Ok(("", tile)) => tile,
+            Ok((rest, _)) => bail!("Unexpected {:?} after {}", rest, header),
+            Err(err) => bail!("Could not parse {}: {}", header, err),
+        };
+        if tiles.insert(id, cells).is_some() {
+            bail!("Tile {} appears twice", id.0);
+        }
+    }
+    if tiles.is_empty() {
+        bail!("There are no tiles");
+    }
+    Ok(Input { tiles })
+}
+
 #[aoc_generator(day20)]
 pub fn input_generator(input: &str) -> Input {
-    println!("parse Input");
-    let (_, tiles): (_, Vec<(TileId, Vec<(usize, usize)>)>) = separated_list1(
-        tuple((line_ending, line_ending)),
-        tuple((parse_tile_header, parse_tile)),
-    )(input)
-    .expect("parser error");
-    println!("parsed Input");
-    Input {
-        tiles: tiles
-            .iter()
-            .cloned()
-            .map(|(key, value)| (key, value))
-            .collect::<HashMap<_, _>>(),
-    }
+    parse_tiles(input).expect("Could not parse the tiles")
 }
+
 #[derive(PartialEq, Copy, Clone, Eq, Debug)]
 enum Neighbor {
     Yes,
@@ -106,7 +145,7 @@ impl Edges {
     }
 }
 
-#[derive(Shrinkwrap, Clone, Copy, PartialEq, Debug, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Debug, Eq, Hash, Serialize, Deserialize)]
 pub struct TileId(usize);
 impl TileId {
     fn flip(&self, map: &mut HashMap<TileId, (Edges, Vec<(usize, usize)>)>) {
@@ -349,14 +388,14 @@ pub fn part1(input: &Input) -> usize {
                 .count()
                 == 2
         })
-        .map(|s| ***s)
+        .map(|s| s.0)
         .product()
 }
 
-#[aoc(day20, part2)]
-pub fn part2(input: &Input) -> usize {
+// Lays the tiles out on a square grid, each one turned and flipped to line up
+// with its top and left neighbours.
+fn assemble(input: &Input) -> (Array2D<Option<TileId>>, OrientedTiles) {
     let size = 10;
-    println!("Contruct tile map");
     let mut tiles = input
         .tiles
         .iter()
@@ -437,8 +476,6 @@ pub fn part2(input: &Input) -> usize {
             (_, _) => corner.rotate(&mut tiles),
         }
     }
-    println!();
-    println!("First row starting with: {}", **corner);
     grid[(0, 0)] = Some(*corner);
     let y = 0;
     for x in 1..grid_size {
@@ -499,8 +536,14 @@ pub fn part2(input: &Input) -> usize {
             ))
         }
     }
+    (grid, tiles)
+}
+
+#[aoc(day20, part2)]
+pub fn part2(input: &Input) -> usize {
+    let (grid, tiles) = assemble(input);
+    let grid_size = grid.num_rows();
 
-    println!("Remove Edges");
     let mut complete_map: Vec<(usize, usize)> = Vec::new();
 
     for (i, s) in grid.elements_row_major_iter().enumerate() {
@@ -586,117 +629,37 @@ pub fn part2(input: &Input) -> usize {
     complete_map.len()
 }
 
+fn explain(part: u32, input: &Input) -> Option<Explanation> {
+    if part != 2 {
+        return None;
+    }
+    let (grid, _) = assemble(input);
+    let rows = grid
+        .rows_iter()
+        .map(|row| {
+            row.map(|id| id.map_or_else(|| "?".to_string(), |id| id.0.to_string()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    Some(Explanation::Grid(rows))
+}
+
+solution!(
+    Day20,
+    20,
+    Input,
+    parse_tiles,
+    part1,
+    part2,
+    explain = explain
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE1: &str = "Tile 2311:
-..##.#..#.
-##..#.....
-#...##..#.
-####.#...#
-##.##.###.
-##...#.###
-.#.#.#..##
-..#....#..
-###...#.#.
-..###..###
-
-Tile 1951:
-#.##...##.
-#.####...#
-.....#..##
-#...######
-.##.#....#
-.###.#####
-###.##.##.
-.###....#.
-..#.#..#.#
-#...##.#..
-
-Tile 1171:
-####...##.
-#..##.#..#
-##.#..#.#.
-.###.####.
-..###.####
-.##....##.
-.#...####.
-#.##.####.
-####..#...
-.....##...
-
-Tile 1427:
-###.##.#..
-.#..#.##..
-.#.##.#..#
-#.#.#.##.#
-....#...##
-...##..##.
-...#.#####
-.#.####.#.
-..#..###.#
-..##.#..#.
-
-Tile 1489:
-##.#.#....
-..##...#..
-.##..##...
-..#...#...
-#####...#.
-#..#.#.#.#
-...#.#.#..
-##.#...##.
-..##.##.##
-###.##.#..
-
-Tile 2473:
-#....####.
-#..#.##...
-#.##..#...
-######.#.#
-.#...#.#.#
-.#########
-.###.#..#.
-########.#
-##...##.#.
-..###.#.#.
-
-Tile 2971:
-..#.#....#
-#...###...
-#.#.###...
-##.##..#..
-.#####..##
-.#..####.#
-#..#.#..#.
-..####.###
-..#.#.###.
-...#.#.#.#
-
-Tile 2729:
-...#.#.#.#
-####.#....
-..#.#.....
-....#..#.#
-.##..##.#.
-.#.####...
-####.#.#..
-##.####...
-##..#.##..
-#.##...##.
-
-Tile 3079:
-#.#.#####.
-.#..######
-..#.......
-######....
-####.#..#.
-.#...#.##.
-#.#####.##
-..#.###...
-..#.......
-..#.###...";
+    const SAMPLE1: &str = include_str!("../examples/day20/sample.txt");
 
     #[test]
     fn sample1() {
@@ -705,6 +668,27 @@ Tile 3079:
         assert_eq!(part1(&input), 20899048083289);
     }
 
+    #[test]
+    fn parse_errors() {
+        let crlf = SAMPLE1.replace('\n', "\r\n");
+        assert_eq!(
+            parse_tiles(&crlf).unwrap().tiles,
+            input_generator(SAMPLE1).tiles
+        );
+        assert!(parse_tiles("Tile 1:\n#.#\n").is_err());
+        assert!(parse_tiles("").is_err());
+        let tile = SAMPLE1.split("\n\n").next().unwrap();
+        assert!(parse_tiles(&format!("{}\n\n{}", tile, tile)).is_err());
+
+        let json = serde_json::to_string(&input_generator(SAMPLE1)).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Input>(&json).unwrap().tiles,
+            input_generator(SAMPLE1).tiles
+        );
+        assert!(serde_json::from_str::<Input>(r#"{"tiles":{"1":[[10,0]]}}"#).is_err());
+        assert!(serde_json::from_str::<Input>(r#"{"tiles":{}}"#).is_err());
+    }
+
     #[test]
     fn sample2() {
         let input = input_generator(SAMPLE1);
@@ -712,6 +696,25 @@ Tile 3079:
         assert_eq!(part2(&input), 273);
     }
 
+    #[test]
+    fn explain_grid() {
+        let input = input_generator(SAMPLE1);
+        let rows = match explain(2, &input) {
+            Some(Explanation::Grid(rows)) => rows,
+            other => panic!("Expected a grid, got {:?}", other),
+        };
+        assert_eq!(rows.len(), 3);
+        let ids = rows
+            .iter()
+            .map(|row| row.split(' ').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut corners = vec![ids[0][0], ids[0][2], ids[2][0], ids[2][2]];
+        corners.sort_unstable();
+        assert_eq!(corners, ["1171", "1951", "2971", "3079"]);
+        assert_eq!(ids[1][1], "1427");
+        assert_eq!(explain(1, &input), None);
+    }
+
     #[test]
     fn test_rotation() {
         let tile = TileId(0);
@@ -724,7 +727,6 @@ Tile 3079:
         let mut tiles = tiles
             .iter()
             .map(|(s, vec)| {
-                println!("{}", **s);
                 let mut top = vec
                     .iter()
                     .filter(|s| s.1 == 0)
@@ -814,7 +816,6 @@ Tile 3079:
         let mut tiles = tiles
             .iter()
             .map(|(s, vec)| {
-                println!("{}", **s);
                 let mut top = vec
                     .iter()
                     .filter(|s| s.1 == 0)
@@ -1,93 +1,155 @@
+use crate::input::Grid;
+use anyhow::{anyhow, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 use array2d::*;
-use std::iter::FromIterator;
+use std::ops::RangeInclusive;
+
+pub fn parse_map(input: &str) -> Result<Grid<bool>> {
+    let rows = input
+        .lines()
+        .map(|s| {
+            s.trim()
+                .chars()
+                .map(|c| match c {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    c => Err(anyhow!("Unexpected {:?} in the map", c)),
+                })
+                .collect()
+        })
+        .collect::<Result<Vec<Vec<bool>>>>()?;
+    Grid::from_rows(&rows).context("Invalid map")
+}
 
 #[aoc_generator(day3)]
 pub fn input_generator(input: &str) -> Array2D<bool> {
-    let rows = Vec::from_iter(
-        input
-            .lines()
-            .map(|s| s.trim().bytes().map(|b| b == b'#').collect()),
-    );
-    Array2D::from_rows(&rows)
+    parse_map(input).expect("Could not parse the map").0
 }
 
-#[aoc(day3, part1)]
-pub fn part1(input: &Array2D<bool>) -> usize {
-    let len = input.num_columns();
-    let mut count = 0;
-    for i in 0..input.num_rows() {
-        if input[(i * 1, i * 3 % len)] {
-            count += 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub right: i64,
+    pub down: usize,
+}
+
+impl Slope {
+    pub fn new(right: i64, down: usize) -> Self {
+        assert!(down > 0, "Slope has to go down");
+        Slope { right, down }
+    }
+
+    // (row, unwrapped column) of every visited square, the column keeps going
+    // past the edge of the map.
+    fn steps(self, map: &Array2D<bool>) -> impl Iterator<Item = (usize, i64)> {
+        (0..map.num_rows())
+            .step_by(self.down)
+            .enumerate()
+            .map(move |(step, row)| (row, step as i64 * self.right))
+    }
+
+    // Visited (row, column) coordinates on the map.
+    pub fn path(self, map: &Array2D<bool>) -> impl Iterator<Item = (usize, usize)> {
+        let len = map.num_columns() as i64;
+        self.steps(map)
+            .map(move |(row, column)| (row, column.rem_euclid(len) as usize))
+    }
+
+    pub fn count_trees(self, map: &Array2D<bool>) -> usize {
+        self.path(map).filter(|position| map[*position]).count()
+    }
+
+    pub fn render(self, map: &Array2D<bool>) -> String {
+        let len = map.num_columns() as i64;
+        let steps: Vec<(usize, i64)> = self.steps(map).collect();
+        // Repeat the pattern as often as needed to show the whole path.
+        let first_tile = steps
+            .iter()
+            .map(|(_, c)| c.div_euclid(len))
+            .min()
+            .unwrap_or(0);
+        let last_tile = steps
+            .iter()
+            .map(|(_, c)| c.div_euclid(len))
+            .max()
+            .unwrap_or(0);
+        let mut out = String::new();
+        for row in 0..map.num_rows() {
+            let visited = steps.iter().find(|(r, _)| *r == row).map(|(_, c)| *c);
+            for column in first_tile * len..(last_tile + 1) * len {
+                let tree = map[(row, column.rem_euclid(len) as usize)];
+                out.push(match (visited == Some(column), tree) {
+                    (true, true) => 'X',
+                    (true, false) => 'O',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                });
+            }
+            out.push('\n');
         }
+        out
     }
-    return count;
+}
+
+// Slopes with the fewest and the most trees, first one wins on ties.
+pub fn slope_extremes(
+    map: &Array2D<bool>,
+    rights: RangeInclusive<i64>,
+    downs: RangeInclusive<usize>,
+) -> Option<((Slope, usize), (Slope, usize))> {
+    let counts: Vec<(Slope, usize)> = downs
+        .filter(|down| *down > 0)
+        .flat_map(|down| rights.clone().map(move |right| Slope::new(right, down)))
+        .map(|slope| (slope, slope.count_trees(map)))
+        .collect();
+    let best = counts.iter().min_by_key(|(_, trees)| *trees)?;
+    let worst = counts.iter().rev().max_by_key(|(_, trees)| *trees)?;
+    Some((*best, *worst))
+}
+
+pub const SLOPES: [Slope; 5] = [
+    Slope { right: 1, down: 1 },
+    Slope { right: 3, down: 1 },
+    Slope { right: 5, down: 1 },
+    Slope { right: 7, down: 1 },
+    Slope { right: 1, down: 2 },
+];
+
+#[aoc(day3, part1)]
+pub fn part1(input: &Array2D<bool>) -> usize {
+    Slope::new(3, 1).count_trees(input)
 }
 
 #[aoc(day3, part2)]
 pub fn part2(input: &Array2D<bool>) -> usize {
-    let len = input.num_columns();
-    let mut count1 = 0;
-    let mut count2 = 0;
-    let mut count3 = 0;
-    let mut count4 = 0;
-    let mut count5 = 0;
-    for i in 0..input.num_rows() {
-        if input[(i * 1, i * 1 % len)] {
-            count1 += 1;
-        }
-        if input[(i * 1, i * 3 % len)] {
-            count2 += 1;
-        }
-        if input[(i * 1, i * 5 % len)] {
-            count3 += 1;
-        }
-        if input[(i * 1, i * 7 % len)] {
-            count4 += 1;
-        }
-    }
-    for i in 0..input.num_rows() / 2 {
-        if input[(i * 2, i * 1 % len)] {
-            count5 += 1;
-        }
-    }
-    return count1 * count2 * count3 * count4 * count5;
+    SLOPES
+        .iter()
+        .map(|slope| slope.count_trees(input))
+        .product()
 }
 
 #[aoc(day3, part2, Iterator)]
 pub fn part2_iterator(input: &Array2D<bool>) -> usize {
-    let len = input.num_columns();
-    let slopes = [(1, 1), (1, 3), (1, 5), (1, 7), (2, 1)];
-    slopes
+    let len = input.num_columns() as i64;
+    SLOPES
         .iter()
-        .map(|&(r, c)| {
-            input
-                .rows_iter()
+        .map(|slope| {
+            (0..input.num_rows())
+                .step_by(slope.down)
                 .enumerate()
-                .step_by(r)
-                .map(|(i, mut row)| row.nth((i / r * c) % len).unwrap_or(&false))
-                .filter(|&b| *b)
+                .filter(|&(i, row)| input[(row, (i as i64 * slope.right).rem_euclid(len) as usize)])
                 .count()
         })
         .product()
 }
 
+solution!(Day3, 3, Grid<bool>, parse_map, part1, part2, 2 "Iterator" => part2_iterator);
+
 #[cfg(test)]
 mod tests {
-    use super::{input_generator, part1, part2, part2_iterator};
-
-    const SAMPLE: &str = "..##.......
-        #...#...#..
-        .#....#..#.
-        ..#.#...#.#
-        .#...##..#.
-        ..#.##.....
-        .#.#.#....#
-        .#........#
-        #.##...#...
-        #...##....#
-        .#..#...#.#";
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../examples/day03/sample.txt");
 
     #[test]
     fn sample1() {
@@ -103,4 +165,27 @@ mod tests {
     fn sample2_iterator() {
         assert_eq!(part2_iterator(&input_generator(&SAMPLE)), 336);
     }
+
+    #[test]
+    fn slopes() {
+        let map = input_generator(SAMPLE);
+        let counts: Vec<usize> = SLOPES.iter().map(|s| s.count_trees(&map)).collect();
+        assert_eq!(counts, vec![2, 7, 3, 4, 2]);
+        assert_eq!(Slope::new(-3, 1).count_trees(&map), 3);
+        assert_eq!(
+            Slope::new(1, 2).path(&map).collect::<Vec<_>>(),
+            vec![(0, 0), (2, 1), (4, 2), (6, 3), (8, 4), (10, 5)]
+        );
+
+        let (best, worst) = slope_extremes(&map, -3..=3, 1..=2).unwrap();
+        assert_eq!(best, (Slope::new(2, 1), 1));
+        assert_eq!(worst, (Slope::new(3, 1), 7));
+
+        let render = Slope::new(3, 1).render(&map);
+        let lines: Vec<&str> = render.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "O.##.........##.........##.......");
+        assert_eq!(lines[1], "#..O#...#..#...#...#..#...#...#..");
+        assert_eq!(render.matches('X').count(), 7);
+    }
 }
@@ -1,178 +1,516 @@
-use anyhow::Result;
+use crate::input;
+use anyhow::{anyhow, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::io::Write;
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Field {
+    Byr,
+    Iyr,
+    Eyr,
+    Hgt,
+    Hcl,
+    Ecl,
+    Pid,
+    Cid,
+}
+
+impl Field {
+    pub const ALL: [Field; 8] = [
+        Field::Byr,
+        Field::Iyr,
+        Field::Eyr,
+        Field::Hgt,
+        Field::Hcl,
+        Field::Ecl,
+        Field::Pid,
+        Field::Cid,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Field::Byr => "byr",
+            Field::Iyr => "iyr",
+            Field::Eyr => "eyr",
+            Field::Hgt => "hgt",
+            Field::Hcl => "hcl",
+            Field::Ecl => "ecl",
+            Field::Pid => "pid",
+            Field::Cid => "cid",
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.key())
+    }
+}
+
+impl FromStr for Field {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Field::ALL
+            .iter()
+            .find(|field| field.key() == s)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown passport field {:?}", s))
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Entry {
-    byr: Option<usize>,
-    iyr: Option<usize>,
-    eyr: Option<usize>,
-    hgt: Option<String>,
-    hcl: Option<String>,
-    ecl: Option<String>,
-    pid: Option<String>,
-    cid: Option<usize>,
+    pub byr: Option<String>,
+    pub iyr: Option<String>,
+    pub eyr: Option<String>,
+    pub hgt: Option<String>,
+    pub hcl: Option<String>,
+    pub ecl: Option<String>,
+    pub pid: Option<String>,
+    pub cid: Option<String>,
+}
+
+impl Entry {
+    fn slot(&mut self, field: Field) -> &mut Option<String> {
+        match field {
+            Field::Byr => &mut self.byr,
+            Field::Iyr => &mut self.iyr,
+            Field::Eyr => &mut self.eyr,
+            Field::Hgt => &mut self.hgt,
+            Field::Hcl => &mut self.hcl,
+            Field::Ecl => &mut self.ecl,
+            Field::Pid => &mut self.pid,
+            Field::Cid => &mut self.cid,
+        }
+    }
+
+    pub fn get(&self, field: Field) -> Option<&str> {
+        match field {
+            Field::Byr => self.byr.as_deref(),
+            Field::Iyr => self.iyr.as_deref(),
+            Field::Eyr => self.eyr.as_deref(),
+            Field::Hgt => self.hgt.as_deref(),
+            Field::Hcl => self.hcl.as_deref(),
+            Field::Ecl => self.ecl.as_deref(),
+            Field::Pid => self.pid.as_deref(),
+            Field::Cid => self.cid.as_deref(),
+        }
+    }
 }
 
 impl FromStr for Entry {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let params: Vec<&str> = s.split(" ").collect();
         let mut build = Entry::default();
-        for param in params {
-            let part: Vec<&str> = param.split(":").collect();
-            match part[0] {
-                "byr" => build.byr = Some(part[1].parse::<usize>()?),
-                "iyr" => build.iyr = Some(part[1].parse::<usize>()?),
-                "eyr" => build.eyr = Some(part[1].parse::<usize>()?),
-                "hgt" => build.hgt = Some(part[1].to_owned()),
-                "hcl" => build.hcl = Some(part[1].to_owned()),
-                "ecl" => build.ecl = Some(part[1].to_owned()),
-                "pid" => build.pid = Some(part[1].to_owned()),
-                "cid" => build.cid = Some(part[1].parse::<usize>()?),
-                _ => unimplemented!(),
+        for param in s.split_whitespace() {
+            let split_pos = param
+                .find(':')
+                .ok_or_else(|| anyhow!("Missing ':' in {:?}", param))?;
+            let field = param[0..split_pos].parse::<Field>()?;
+            let slot = build.slot(field);
+            if slot.is_some() {
+                return Err(anyhow!("Duplicate passport field {}", field));
             }
+            *slot = Some(param[split_pos + 1..].to_owned());
         }
-        return Ok(build);
+        Ok(build)
     }
 }
 
-#[aoc_generator(day4)]
-pub fn input_generator(input: &str) -> Vec<Entry> {
-    let mut entries: Vec<Entry> = Vec::new();
-    for line in input.split("\n\n") {
-        match line.replace("\n", " ").parse::<Entry>() {
-            Ok(entry) => entries.push(entry),
-            Err(err) => {
-                dbg!(err, &line);
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    Any,
+    Year(RangeInclusive<u32>),
+    Height {
+        cm: RangeInclusive<u32>,
+        inches: RangeInclusive<u32>,
+    },
+    HexColour,
+    Enum(Vec<String>),
+    Digits(usize),
+}
+
+impl FieldType {
+    pub fn check(&self, value: &str) -> std::result::Result<(), String> {
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        match self {
+            FieldType::Any => Ok(()),
+            FieldType::Year(range) => {
+                if value.len() != 4 || !is_digits(value) {
+                    return Err("not a four digit year".to_owned());
+                }
+                let year = value.parse::<u32>().unwrap();
+                if range.contains(&year) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} is not in {}-{}",
+                        year,
+                        range.start(),
+                        range.end()
+                    ))
+                }
+            }
+            FieldType::Height { cm, inches } => {
+                let (number, range) = if let Some(number) = value.strip_suffix("cm") {
+                    (number, cm)
+                } else if let Some(number) = value.strip_suffix("in") {
+                    (number, inches)
+                } else {
+                    return Err("missing unit cm or in".to_owned());
+                };
+                if !is_digits(number) {
+                    return Err(format!("{:?} is not a number", number));
+                }
+                match number.parse::<u32>() {
+                    Ok(height) if range.contains(&height) => Ok(()),
+                    _ => Err(format!(
+                        "{} is not in {}-{}",
+                        value,
+                        range.start(),
+                        range.end()
+                    )),
+                }
+            }
+            FieldType::HexColour => {
+                if value.len() == 7
+                    && value.starts_with('#')
+                    && value[1..]
+                        .bytes()
+                        .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+                {
+                    Ok(())
+                } else {
+                    Err("not a colour like #1a2b3c".to_owned())
+                }
+            }
+            FieldType::Enum(values) => {
+                if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(format!("not one of {}", values.join(", ")))
+                }
+            }
+            FieldType::Digits(len) => {
+                if value.len() == *len && is_digits(value) {
+                    Ok(())
+                } else {
+                    Err(format!("not a {} digit number", len))
+                }
             }
         }
     }
-    return entries;
 }
 
-#[aoc(day4, part1)]
-pub fn part1(input: &[Entry]) -> usize {
-    input
-        .iter()
-        .filter_map(|e| {
-            if e.byr.is_some()
-                && e.iyr.is_some()
-                && e.eyr.is_some()
-                && e.hgt.is_some()
-                && e.hcl.is_some()
-                && e.ecl.is_some()
-                && e.pid.is_some()
-            {
-                Some(e)
-            } else {
-                None
-            }
-        })
-        .count()
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRule {
+    pub field: Field,
+    pub required: bool,
+    pub kind: FieldType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleFailure {
+    Missing(Field),
+    Invalid {
+        field: Field,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for RuleFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleFailure::Missing(field) => write!(f, "{}: missing", field),
+            RuleFailure::Invalid {
+                field,
+                value,
+                reason,
+            } => write!(f, "{}: {:?} {}", field, value, reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PassportSchema {
+    pub rules: Vec<FieldRule>,
+}
+
+impl PassportSchema {
+    pub fn new() -> Self {
+        PassportSchema { rules: Vec::new() }
+    }
+
+    pub fn field(mut self, field: Field, required: bool, kind: FieldType) -> Self {
+        self.rules.push(FieldRule {
+            field,
+            required,
+            kind,
+        });
+        self
+    }
+
+    // Part 1: every field but cid has to be present.
+    pub fn presence() -> Self {
+        Field::ALL
+            .iter()
+            .fold(PassportSchema::new(), |schema, field| {
+                schema.field(*field, *field != Field::Cid, FieldType::Any)
+            })
+    }
+
+    // Part 2: present and well-formed.
+    pub fn strict() -> Self {
+        PassportSchema::new()
+            .field(Field::Byr, true, FieldType::Year(1920..=2002))
+            .field(Field::Iyr, true, FieldType::Year(2010..=2020))
+            .field(Field::Eyr, true, FieldType::Year(2020..=2030))
+            .field(
+                Field::Hgt,
+                true,
+                FieldType::Height {
+                    cm: 150..=193,
+                    inches: 59..=76,
+                },
+            )
+            .field(Field::Hcl, true, FieldType::HexColour)
+            .field(
+                Field::Ecl,
+                true,
+                FieldType::Enum(
+                    ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                ),
+            )
+            .field(Field::Pid, true, FieldType::Digits(9))
+            .field(Field::Cid, false, FieldType::Any)
+    }
+
+    pub fn validate(&self, entry: &Entry) -> Vec<RuleFailure> {
+        self.rules
+            .iter()
+            .filter_map(|rule| match entry.get(rule.field) {
+                None if rule.required => Some(RuleFailure::Missing(rule.field)),
+                None => None,
+                Some(value) => rule
+                    .kind
+                    .check(value)
+                    .err()
+                    .map(|reason| RuleFailure::Invalid {
+                        field: rule.field,
+                        value: value.to_owned(),
+                        reason,
+                    }),
+            })
+            .collect()
+    }
+
+    pub fn is_valid(&self, entry: &Entry) -> bool {
+        self.validate(entry).is_empty()
+    }
 }
-fn is_valid_hgt(input: &str) -> bool {
-    match input[0..input.len() - 2].parse::<usize>() {
-        Ok(hgt) => match input[input.len() - 2..input.len()].as_ref() {
-            "cm" => hgt >= 150 && hgt <= 193,
-            "in" => hgt >= 59 && hgt <= 76,
-            _ => false,
-        },
-        Err(_err) => false,
+
+// Heights are normalised to centimetres.
+pub fn height_cm(value: &str) -> Option<f64> {
+    if let Some(number) = value.strip_suffix("cm") {
+        number.parse::<f64>().ok()
+    } else if let Some(number) = value.strip_suffix("in") {
+        number.parse::<f64>().ok().map(|inches| inches * 2.54)
+    } else {
+        None
     }
 }
-fn is_valid_hcl(input: &str) -> bool {
-    return input.starts_with("#")
-        && input[1..input.len()]
-            .bytes()
-            .all(|b| (b >= b'0' && b <= b'9') || (b >= b'a' && b <= b'f'));
+
+pub fn parse_batch(input: &str) -> Vec<(String, Result<Entry>)> {
+    input::blocks(input)
+        .into_iter()
+        .map(|block| (block.to_owned(), block.parse::<Entry>()))
+        .collect()
 }
-fn is_valid_ecl(input: &str) -> bool {
-    match input {
-        "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-        _ => false,
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRecord {
+    pub index: usize,
+    pub byr: Option<String>,
+    pub iyr: Option<String>,
+    pub eyr: Option<String>,
+    pub hgt: Option<String>,
+    pub hcl: Option<String>,
+    pub ecl: Option<String>,
+    pub pid: Option<String>,
+    pub cid: Option<String>,
+    pub height_cm: Option<f64>,
+    pub valid: bool,
+    pub failures: Vec<RuleFailure>,
+    pub error: Option<String>,
+    pub raw: Option<String>,
+}
+
+// An `ExportRecord` as one flat CSV row.
+#[derive(Serialize)]
+struct CsvRecord<'a> {
+    index: usize,
+    byr: Option<&'a str>,
+    iyr: Option<&'a str>,
+    eyr: Option<&'a str>,
+    hgt: Option<&'a str>,
+    hcl: Option<&'a str>,
+    ecl: Option<&'a str>,
+    pid: Option<&'a str>,
+    cid: Option<&'a str>,
+    height_cm: Option<f64>,
+    valid: bool,
+    #[serde(serialize_with = "join_failures")]
+    failures: &'a [RuleFailure],
+    error: Option<&'a str>,
+    raw: Option<&'a str>,
+}
+
+impl<'a> From<&'a ExportRecord> for CsvRecord<'a> {
+    fn from(record: &'a ExportRecord) -> Self {
+        CsvRecord {
+            index: record.index,
+            byr: record.byr.as_deref(),
+            iyr: record.iyr.as_deref(),
+            eyr: record.eyr.as_deref(),
+            hgt: record.hgt.as_deref(),
+            hcl: record.hcl.as_deref(),
+            ecl: record.ecl.as_deref(),
+            pid: record.pid.as_deref(),
+            cid: record.cid.as_deref(),
+            height_cm: record.height_cm,
+            valid: record.valid,
+            failures: &record.failures,
+            error: record.error.as_deref(),
+            raw: record.raw.as_deref(),
+        }
     }
 }
-fn is_valid_pid(input: &str) -> bool {
-    return input.len() == 9 && input.bytes().all(|b| b >= b'0' && b <= b'9');
+
+// Failures are joined into one column, so that the CSV export stays flat.
+fn join_failures<S: Serializer>(
+    failures: &[RuleFailure],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let failures: Vec<String> = failures.iter().map(|f| f.to_string()).collect();
+    serializer.serialize_str(&failures.join("; "))
 }
 
-#[aoc(day4, part2)]
-pub fn part2(input: &[Entry]) -> usize {
-    input
-        .iter()
-        .filter_map(|e| {
-            if e.byr.is_some()
-                && e.iyr.is_some()
-                && e.eyr.is_some()
-                && e.hgt.is_some()
-                && e.hcl.is_some()
-                && e.ecl.is_some()
-                && e.pid.is_some()
-                && e.byr.unwrap() >= 1920
-                && e.byr.unwrap() <= 2002
-                && e.iyr.unwrap() >= 2010
-                && e.iyr.unwrap() <= 2020
-                && e.eyr.unwrap() >= 2020
-                && e.eyr.unwrap() <= 2030
-                && is_valid_hgt(e.hgt.as_ref().unwrap())
-                && is_valid_hcl(e.hcl.as_ref().unwrap())
-                && is_valid_ecl(e.ecl.as_ref().unwrap())
-                && is_valid_pid(e.pid.as_ref().unwrap())
-            {
-                Some(e)
-            } else {
-                None
+pub fn export_records(input: &str, schema: &PassportSchema) -> Vec<ExportRecord> {
+    parse_batch(input)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (raw, entry))| match entry {
+            Ok(entry) => {
+                let failures = schema.validate(&entry);
+                ExportRecord {
+                    index,
+                    height_cm: entry.hgt.as_deref().and_then(height_cm),
+                    valid: failures.is_empty(),
+                    failures,
+                    error: None,
+                    raw: None,
+                    byr: entry.byr,
+                    iyr: entry.iyr,
+                    eyr: entry.eyr,
+                    hgt: entry.hgt,
+                    hcl: entry.hcl,
+                    ecl: entry.ecl,
+                    pid: entry.pid,
+                    cid: entry.cid,
+                }
             }
+            Err(err) => ExportRecord {
+                index,
+                byr: None,
+                iyr: None,
+                eyr: None,
+                hgt: None,
+                hcl: None,
+                ecl: None,
+                pid: None,
+                cid: None,
+                height_cm: None,
+                valid: false,
+                failures: Vec::new(),
+                error: Some(err.to_string()),
+                raw: Some(raw),
+            },
         })
-        .count()
+        .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{input_generator, part1, part2};
-
-    const SAMPLE: &str = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
-byr:1937 iyr:2017 cid:147 hgt:183cm
-
-iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
-hcl:#cfa07d byr:1929
+pub fn write_json_lines<W: Write>(records: &[ExportRecord], mut writer: W) -> Result<()> {
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
 
-hcl:#ae17e1 iyr:2013
-eyr:2024
-ecl:brn pid:760753108 byr:1931
-hgt:179cm
+pub fn write_csv<W: Write>(records: &[ExportRecord], writer: W) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for record in records {
+        writer.serialize(CsvRecord::from(record))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
 
-hcl:#cfa07d eyr:2025 pid:166559648
-iyr:2011 ecl:brn hgt:59in";
-    const SAMPLE_INVALID: &str = "eyr:1972 cid:100
-hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+// Unlike `export_records`, which keeps malformed passports as records with
+// an error, the solvers need every passport to parse.
+pub fn parse_passports(input: &str) -> Result<Vec<Entry>> {
+    parse_batch(input)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (_, entry))| entry.with_context(|| format!("Passport {}", index)))
+        .collect()
+}
 
-iyr:2019
-hcl:#602927 eyr:1967 hgt:170cm
-ecl:grn pid:012533040 byr:1946
+#[aoc_generator(day4)]
+pub fn input_generator(input: &str) -> Vec<Entry> {
+    parse_passports(input).expect("Could not parse passports")
+}
 
-hcl:dab227 iyr:2012
-ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+#[aoc(day4, part1)]
+pub fn part1(input: &[Entry]) -> usize {
+    let schema = PassportSchema::presence();
+    input.iter().filter(|e| schema.is_valid(e)).count()
+}
 
-hgt:59cm ecl:zzz
-eyr:2038 hcl:74454a iyr:2023
-pid:3556412378 byr:2007";
+#[aoc(day4, part2)]
+pub fn part2(input: &[Entry]) -> usize {
+    let schema = PassportSchema::strict();
+    input.iter().filter(|e| schema.is_valid(e)).count()
+}
 
-    const SAMPLE_VALID: &str = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
-hcl:#623a2f
+solution!(Day4, 4, Vec<Entry>, parse_passports, part1, part2);
 
-eyr:2029 ecl:blu cid:129 byr:1989
-iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-hcl:#888785
-hgt:164cm byr:2001 iyr:2015 cid:88
-pid:545766238 ecl:hzl
-eyr:2022
+    const SAMPLE: &str = include_str!("../examples/day04/sample.txt");
+    const SAMPLE_INVALID: &str = include_str!("../examples/day04/invalid.txt");
 
-iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+    const SAMPLE_VALID: &str = include_str!("../examples/day04/valid.txt");
 
     #[test]
     fn sample1() {
@@ -184,4 +522,64 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
         assert_eq!(part2(&input_generator(&SAMPLE_INVALID)), 0);
         assert_eq!(part2(&input_generator(&SAMPLE_VALID)), 4);
     }
+
+    #[test]
+    fn failed_rules() {
+        let input = input_generator(SAMPLE_INVALID);
+        let failures = PassportSchema::strict().validate(&input[0]);
+        assert_eq!(
+            failures.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+            vec![
+                "eyr: \"1972\" 1972 is not in 2020-2030",
+                "hgt: \"170\" missing unit cm or in",
+                "pid: \"186cm\" not a 9 digit number",
+            ]
+        );
+        assert_eq!(
+            PassportSchema::presence().validate(&input_generator(SAMPLE)[1]),
+            vec![RuleFailure::Missing(Field::Hgt)]
+        );
+        assert!("byr:1937 foo:bar".parse::<Entry>().is_err());
+        assert!(parse_passports(&format!("{}\n\nbyr:1937 foo:bar", SAMPLE)).is_err());
+    }
+
+    #[test]
+    fn export() {
+        let input = format!("{}\n\nbyr:1937 foo:bar", SAMPLE);
+        let records = export_records(&input, &PassportSchema::strict());
+        assert_eq!(records.len(), 5);
+        assert!(records[0].valid);
+        assert_eq!(records[0].height_cm, Some(183.0));
+        assert_eq!(records[3].height_cm, Some(59.0 * 2.54));
+        assert_eq!(records[1].failures, vec![RuleFailure::Missing(Field::Hgt)]);
+        assert_eq!(
+            records[4].error.as_deref(),
+            Some("Unknown passport field \"foo\"")
+        );
+
+        let mut json = Vec::new();
+        write_json_lines(&records, &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json.lines().count(), 5);
+        assert!(json
+            .lines()
+            .nth(4)
+            .unwrap()
+            .contains("\"raw\":\"byr:1937 foo:bar\""));
+        assert!(json
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains(r#""failures":[{"missing":"hgt"}]"#));
+        assert!(json.lines().next().unwrap().contains(r#""failures":[]"#));
+
+        let mut csv = Vec::new();
+        write_csv(&records, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 6);
+        assert!(csv.starts_with("index,byr,iyr,eyr,hgt,hcl,ecl,pid,cid,height_cm,valid,"));
+        assert!(
+            csv.contains("\n1,1929,2013,2023,,#cfa07d,amb,028048884,350,,false,hgt: missing,,\n")
+        );
+    }
 }
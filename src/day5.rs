@@ -1,76 +1,288 @@
-use anyhow::Result;
+use crate::solution::Explanation;
+use anyhow::{anyhow, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
-use itertools::Itertools;
-use std::iter::FromIterator;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::str::FromStr;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaneLayout {
+    pub row_bits: u32,
+    pub column_bits: u32,
+}
+
+impl PlaneLayout {
+    pub const STANDARD: PlaneLayout = PlaneLayout {
+        row_bits: 7,
+        column_bits: 3,
+    };
+
+    // Every seat of the plane gets an entry in the occupancy table.
+    pub const MAX_BITS: u32 = 20;
+
+    pub fn new(row_bits: u32, column_bits: u32) -> Result<Self> {
+        if row_bits + column_bits == 0 || row_bits + column_bits > Self::MAX_BITS {
+            return Err(anyhow!(
+                "Layout needs between 1 and {} bits, got {}",
+                Self::MAX_BITS,
+                row_bits + column_bits
+            ));
+        }
+        Ok(PlaneLayout {
+            row_bits,
+            column_bits,
+        })
+    }
+
+    pub fn code_len(&self) -> usize {
+        (self.row_bits + self.column_bits) as usize
+    }
+
+    pub fn rows(&self) -> usize {
+        1 << self.row_bits
+    }
+
+    pub fn columns(&self) -> usize {
+        1 << self.column_bits
+    }
+
+    pub fn seats(&self) -> usize {
+        self.rows() * self.columns()
+    }
+}
+
+impl Default for PlaneLayout {
+    fn default() -> Self {
+        PlaneLayout::STANDARD
+    }
+}
+
+// Parsed inputs only hold standard boarding passes, so a deserialized seat has
+// to fit the standard layout like a decoded one does.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "SeatFields")]
 pub struct Seat {
-    row: u8,
-    column: u8,
+    pub row: u32,
+    pub column: u32,
 }
+
+#[derive(Deserialize)]
+struct SeatFields {
+    row: u32,
+    column: u32,
+}
+
+impl TryFrom<SeatFields> for Seat {
+    type Error = anyhow::Error;
+
+    fn try_from(fields: SeatFields) -> Result<Self> {
+        let layout = PlaneLayout::STANDARD;
+        if fields.row as usize >= layout.rows() || fields.column as usize >= layout.columns() {
+            return Err(anyhow!(
+                "Seat {}/{} is outside of a plane with {} rows of {} seats",
+                fields.row,
+                fields.column,
+                layout.rows(),
+                layout.columns()
+            ));
+        }
+        Ok(Seat {
+            row: fields.row,
+            column: fields.column,
+        })
+    }
+}
+
+impl Seat {
+    pub fn from_id(id: usize, layout: &PlaneLayout) -> Self {
+        Seat {
+            row: (id >> layout.column_bits) as u32,
+            column: (id & (layout.columns() - 1)) as u32,
+        }
+    }
+
+    pub fn id(&self, layout: &PlaneLayout) -> usize {
+        ((self.row as usize) << layout.column_bits) | self.column as usize
+    }
+}
+
 impl FromStr for Seat {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        return Ok(Seat {
-            row: s[0..=6].bytes().enumerate().fold(0u8, |acc, x| match x.1 {
-                b'F' => acc,
-                b'B' => acc | 1 << (6 - x.0),
-                _ => unimplemented!(),
-            }),
-            column: s[7..=9].bytes().enumerate().fold(0u8, |acc, x| match x.1 {
-                b'L' => acc,
-                b'R' => acc | 1 << (2 - x.0),
-                _ => unimplemented!(),
-            }),
-        });
+        BoardingPass::default().decode(s)
     }
 }
 
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BoardingPass {
+    pub layout: PlaneLayout,
+}
+
+impl BoardingPass {
+    pub fn new(layout: PlaneLayout) -> Self {
+        BoardingPass { layout }
+    }
+
+    pub fn decode(&self, code: &str) -> Result<Seat> {
+        if code.chars().count() != self.layout.code_len() {
+            return Err(anyhow!(
+                "Expected a {} character code, got {:?}",
+                self.layout.code_len(),
+                code
+            ));
+        }
+        let mut seat = Seat::default();
+        for (i, c) in code.chars().enumerate() {
+            if i < self.layout.row_bits as usize {
+                seat.row = (seat.row << 1)
+                    | match c {
+                        'F' => 0,
+                        'B' => 1,
+                        _ => return Err(anyhow!("Invalid row character {:?} at {}", c, i)),
+                    };
+            } else {
+                seat.column = (seat.column << 1)
+                    | match c {
+                        'L' => 0,
+                        'R' => 1,
+                        _ => return Err(anyhow!("Invalid column character {:?} at {}", c, i)),
+                    };
+            }
+        }
+        Ok(seat)
+    }
+
+    pub fn decode_id(&self, code: &str) -> Result<usize> {
+        Ok(self.decode(code)?.id(&self.layout))
+    }
+
+    pub fn encode(&self, seat_id: usize) -> Result<String> {
+        if seat_id >= self.layout.seats() {
+            return Err(anyhow!(
+                "Seat id {} is outside of a plane with {} seats",
+                seat_id,
+                self.layout.seats()
+            ));
+        }
+        let len = self.layout.code_len();
+        Ok((0..len)
+            .map(|i| {
+                let bit = (seat_id >> (len - 1 - i)) & 1 == 1;
+                match (i < self.layout.row_bits as usize, bit) {
+                    (true, false) => 'F',
+                    (true, true) => 'B',
+                    (false, false) => 'L',
+                    (false, true) => 'R',
+                }
+            })
+            .collect())
+    }
+
+    // Every seat id of the plane that is not taken.
+    pub fn missing_seats(&self, taken: impl IntoIterator<Item = usize>) -> Vec<usize> {
+        let taken = self.occupancy(taken);
+        (0..self.layout.seats()).filter(|id| !taken[*id]).collect()
+    }
+
+    fn occupancy(&self, taken: impl IntoIterator<Item = usize>) -> Vec<bool> {
+        let mut occupied = vec![false; self.layout.seats()];
+        for id in taken {
+            if let Some(seat) = occupied.get_mut(id) {
+                *seat = true;
+            }
+        }
+        occupied
+    }
+
+    // One line per row, '#' for taken and '.' for free seats with the aisle in
+    // the middle.
+    pub fn seating_chart(&self, taken: impl IntoIterator<Item = usize>) -> String {
+        let occupied = self.occupancy(taken);
+        let columns = self.layout.columns();
+        let width = format!("{}", self.layout.rows() - 1).len();
+        let mut chart = String::new();
+        for (row, seats) in occupied.chunks(columns).enumerate() {
+            chart.push_str(&format!("{:>width$} ", row, width = width));
+            for (column, seat) in seats.iter().enumerate() {
+                if columns > 1 && column == columns / 2 {
+                    chart.push(' ');
+                }
+                chart.push(if *seat { '#' } else { '.' });
+            }
+            chart.push('\n');
+        }
+        chart
+    }
+}
+
+pub fn parse_passes(input: &str, codec: &BoardingPass) -> Result<Vec<Seat>> {
+    let seats = input
+        .lines()
+        .map(|s| codec.decode(s.trim()))
+        .collect::<Result<Vec<Seat>>>()?;
+    if seats.is_empty() {
+        return Err(anyhow!("No boarding passes"));
+    }
+    Ok(seats)
+}
+
 #[aoc_generator(day5)]
-pub fn input_generator(input: &str) -> Vec<String> {
-    Vec::from_iter(input.lines().map(|s| s.trim().to_owned()))
+pub fn input_generator(input: &str) -> Vec<Seat> {
+    parse_passes(input, &BoardingPass::default()).expect("Could not decode boarding pass")
+}
+
+fn seat_ids(input: &[Seat]) -> impl Iterator<Item = usize> + '_ {
+    input.iter().map(|seat| seat.id(&PlaneLayout::STANDARD))
 }
 
 #[aoc(day5, part1)]
-pub fn part1(input: &[String]) -> usize {
-    input
-        .iter()
-        .map(|s| Seat::from_str(s).unwrap())
-        .map(|s| s.row as usize * 8 + s.column as usize)
-        .max()
-        .unwrap()
+pub fn part1(input: &[Seat]) -> usize {
+    seat_ids(input).max().expect("No boarding passes")
 }
 
 #[aoc(day5, part2)]
-pub fn part2(input: &[String]) -> usize {
-    input
-        .iter()
-        .map(|s| Seat::from_str(s).unwrap())
-        .map(|s| s.row as usize * 8 + s.column as usize)
-        .sorted()
-        .tuple_windows::<(_, _)>()
-        .filter_map(|(prev, next)| {
-            if prev + 2 == next {
-                Some(prev + 1)
-            } else {
-                None
-            }
-        })
-        .next()
-        .unwrap()
+pub fn part2(input: &[Seat]) -> usize {
+    let codec = BoardingPass::default();
+    let taken: HashSet<usize> = seat_ids(input).collect();
+    codec
+        .missing_seats(taken.iter().copied())
+        .into_iter()
+        .find(|id| *id > 0 && taken.contains(&(id - 1)) && taken.contains(&(id + 1)))
+        .expect("No free seat between two taken ones")
+}
+
+fn explain(part: u32, input: &[Seat]) -> Option<Explanation> {
+    let id = match part {
+        1 => part1(input),
+        2 => part2(input),
+        _ => return None,
+    };
+    let seat = Seat::from_id(id, &PlaneLayout::STANDARD);
+    Some(Explanation::Seat {
+        row: seat.row,
+        column: seat.column,
+        id,
+    })
 }
 
+solution!(
+    Day5,
+    5,
+    Vec<Seat>,
+    |input| parse_passes(input, &BoardingPass::default()),
+    part1,
+    part2,
+    explain = explain
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &str = "FBFBBFFRLR
-    BFFFBBFRRR
-    FFFBBBFRRR
-    BBFFBBFRLL";
+    const SAMPLE: &str = include_str!("../examples/day05/sample.txt");
 
     #[test]
     fn sample1() {
@@ -78,11 +290,49 @@ mod tests {
         assert_eq!(
             input
                 .iter()
-                .map(|s| Seat::from_str(s).unwrap())
                 .map(|s| s.row as usize * 8 + s.column as usize)
                 .collect::<Vec<usize>>(),
             vec![357, 567, 119, 820]
         );
+        assert!(parse_passes("FBFBBFFRLR\nFBFBBFFRLX", &BoardingPass::default()).is_err());
         assert_eq!(part1(&input), 820);
     }
+
+    #[test]
+    fn sample2() {
+        let codec = BoardingPass::default();
+        let codes: Vec<String> = [350, 351, 353, 354]
+            .iter()
+            .map(|id| codec.encode(*id).unwrap())
+            .collect();
+        assert_eq!(codes[0], "FBFBFBBRRL");
+        assert_eq!(part2(&input_generator(&codes.join("\n"))), 352);
+    }
+
+    #[test]
+    fn codec() {
+        let codec = BoardingPass::new(PlaneLayout::new(2, 2).unwrap());
+        assert_eq!(codec.encode(6).unwrap(), "FBRL");
+        assert_eq!(codec.decode_id("FBRL").unwrap(), 6);
+        assert!(codec.encode(16).is_err());
+        assert!(codec.decode("FBRLL").is_err());
+        assert!(codec.decode("FXRL").is_err());
+        assert!(Seat::from_str("FBFBBFFRLX").is_err());
+        assert_eq!(Seat::from_id(6, &codec.layout), Seat { row: 1, column: 2 });
+        assert!(PlaneLayout::new(16, 16).is_err());
+        assert_eq!(
+            serde_json::from_str::<Seat>(r#"{"row":44,"column":5}"#).unwrap(),
+            Seat { row: 44, column: 5 }
+        );
+        assert!(serde_json::from_str::<Seat>(r#"{"row":44,"column":8}"#).is_err());
+
+        assert_eq!(
+            codec.missing_seats(vec![0, 1, 2, 3, 6]),
+            vec![4, 5, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+        );
+        assert_eq!(
+            codec.seating_chart(vec![0, 1, 2, 3, 6]),
+            "0 ## ##\n1 .. #.\n2 .. ..\n3 .. ..\n"
+        );
+    }
 }
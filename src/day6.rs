@@ -1,66 +1,292 @@
+use crate::input;
+use anyhow::{anyhow, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 
-#[aoc_generator(day6)]
-pub fn input_generator(input: &str) -> Vec<Vec<u32>> {
-    let mut entries: Vec<Vec<u32>> = Vec::new();
-    for group in input.split("\n\n") {
-        entries.push(
-            group
-                .lines()
-                .map(|s| {
-                    s.trim()
-                        .bytes()
-                        .fold(0u32, |acc, x| acc | 1 << (x as usize - 0x61))
-                })
-                .collect(),
-        );
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    chars: Vec<char>,
+    index: HashMap<char, usize>,
+}
+
+impl Alphabet {
+    pub fn new(chars: impl IntoIterator<Item = char>) -> Result<Self> {
+        let chars: Vec<char> = chars.into_iter().collect();
+        let mut index = HashMap::new();
+        for (i, c) in chars.iter().enumerate() {
+            if index.insert(*c, i).is_some() {
+                return Err(anyhow!("Duplicate character {:?} in alphabet", c));
+            }
+        }
+        Ok(Alphabet { chars, index })
+    }
+
+    pub fn lowercase() -> Self {
+        Alphabet::new('a'..='z').unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn index(&self, c: char) -> Option<usize> {
+        self.index.get(&c).copied()
+    }
+
+    pub fn char(&self, index: usize) -> Option<char> {
+        self.chars.get(index).copied()
+    }
+
+    pub fn parse(&self, answers: &str) -> Result<AnswerSet> {
+        let mut set = AnswerSet::new();
+        for c in answers.chars() {
+            let index = self
+                .index(c)
+                .ok_or_else(|| anyhow!("Unexpected answer {:?} in {:?}", c, answers))?;
+            set.insert(index);
+        }
+        Ok(set)
     }
-    return entries;
 }
 
-#[aoc(day6, part1)]
-pub fn part1(input: &[Vec<u32>]) -> u32 {
-    input
-        .iter()
-        .map(|group| group.iter().fold(0u32, |acc, x| acc | x))
-        .map(|x| x.count_ones())
-        .sum()
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::lowercase()
+    }
 }
 
-#[aoc(day6, part2)]
-pub fn part2(input: &[Vec<u32>]) -> u32 {
-    input
-        .iter()
+// A single word while the alphabet fits into 64 bits, a growing vector of
+// words beyond that. Serialized as the indices it contains, which have to be
+// within the lowercase alphabet the puzzle uses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "Vec<usize>", try_from = "Vec<usize>")]
+pub enum AnswerSet {
+    Small(u64),
+    Large(Vec<u64>),
+}
+
+impl AnswerSet {
+    pub fn new() -> Self {
+        AnswerSet::Small(0)
+    }
+
+    fn from_words(mut words: Vec<u64>) -> Self {
+        while words.len() > 1 && *words.last().unwrap() == 0 {
+            words.pop();
+        }
+        match words.len() {
+            0 => AnswerSet::Small(0),
+            1 => AnswerSet::Small(words[0]),
+            _ => AnswerSet::Large(words),
+        }
+    }
+
+    fn words(&self) -> &[u64] {
+        match self {
+            AnswerSet::Small(word) => std::slice::from_ref(word),
+            AnswerSet::Large(words) => words,
+        }
+    }
+
+    fn word(&self, i: usize) -> u64 {
+        self.words().get(i).copied().unwrap_or(0)
+    }
+
+    fn zip_with(&self, other: &AnswerSet, f: impl Fn(u64, u64) -> u64) -> AnswerSet {
+        let len = self.words().len().max(other.words().len());
+        AnswerSet::from_words((0..len).map(|i| f(self.word(i), other.word(i))).collect())
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        let (word, bit) = (index / 64, index % 64);
+        match self {
+            AnswerSet::Small(w) if word == 0 => *w |= 1 << bit,
+            _ => {
+                let mut words = self.words().to_vec();
+                words.resize(words.len().max(word + 1), 0);
+                words[word] |= 1 << bit;
+                *self = AnswerSet::Large(words);
+            }
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.word(index / 64) & (1 << (index % 64)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.words().iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words().iter().all(|w| *w == 0)
+    }
+
+    pub fn union(&self, other: &AnswerSet) -> AnswerSet {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &AnswerSet) -> AnswerSet {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    pub fn symmetric_difference(&self, other: &AnswerSet) -> AnswerSet {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words().iter().enumerate().flat_map(|(i, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+
+    pub fn to_string(&self, alphabet: &Alphabet) -> String {
+        self.iter().filter_map(|i| alphabet.char(i)).collect()
+    }
+}
+
+impl Default for AnswerSet {
+    fn default() -> Self {
+        AnswerSet::new()
+    }
+}
+
+impl PartialEq for AnswerSet {
+    fn eq(&self, other: &Self) -> bool {
+        let len = self.words().len().max(other.words().len());
+        (0..len).all(|i| self.word(i) == other.word(i))
+    }
+}
+
+impl Eq for AnswerSet {}
+
+impl From<AnswerSet> for Vec<usize> {
+    fn from(set: AnswerSet) -> Self {
+        set.iter().collect()
+    }
+}
+
+impl TryFrom<Vec<usize>> for AnswerSet {
+    type Error = anyhow::Error;
+
+    fn try_from(indices: Vec<usize>) -> Result<Self> {
+        let letters = Alphabet::lowercase().len();
+        let mut set = AnswerSet::new();
+        for index in indices {
+            if index >= letters {
+                return Err(anyhow!(
+                    "Answer {} is outside the {} letters of the alphabet",
+                    index,
+                    letters
+                ));
+            }
+            set.insert(index);
+        }
+        Ok(set)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    pub members: Vec<AnswerSet>,
+}
+
+impl Group {
+    // Answered by anyone.
+    pub fn union(&self) -> AnswerSet {
+        self.members
+            .iter()
+            .fold(AnswerSet::new(), |acc, member| acc.union(member))
+    }
+
+    // Answered by everyone.
+    pub fn intersection(&self) -> AnswerSet {
+        let mut iter = self.members.iter();
+        match iter.next() {
+            Some(first) => iter.fold(first.clone(), |acc, member| acc.intersection(member)),
+            None => AnswerSet::new(),
+        }
+    }
+
+    // Answered by an odd number of members.
+    pub fn symmetric_difference(&self) -> AnswerSet {
+        self.members.iter().fold(AnswerSet::new(), |acc, member| {
+            acc.symmetric_difference(member)
+        })
+    }
+
+    pub fn at_least(&self, k: usize) -> AnswerSet {
+        let mut counts: Vec<usize> = Vec::new();
+        for member in self.members.iter() {
+            for i in member.iter() {
+                if counts.len() <= i {
+                    counts.resize(i + 1, 0);
+                }
+                counts[i] += 1;
+            }
+        }
+        let mut set = AnswerSet::new();
+        counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count >= k.max(1))
+            .for_each(|(i, _)| set.insert(i));
+        set
+    }
+}
+
+pub fn parse_groups(input: &str, alphabet: &Alphabet) -> Result<Vec<Group>> {
+    input::blocks(input)
+        .into_iter()
         .map(|group| {
-            // fold_first workaround
-            let mut iter = group.iter();
-            let first = *iter.next().unwrap();
-            iter.fold(first, |acc, x| acc & x)
+            Ok(Group {
+                members: group
+                    .lines()
+                    .map(|s| alphabet.parse(s.trim()))
+                    .collect::<Result<Vec<AnswerSet>>>()?,
+            })
         })
-        .map(|x| x.count_ones())
-        .sum()
+        .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[aoc_generator(day6)]
+pub fn input_generator(input: &str) -> Vec<Group> {
+    parse_groups(input, &Alphabet::lowercase()).expect("Could not parse answers")
+}
 
-    const SAMPLE: &str = "abc
+#[aoc(day6, part1)]
+pub fn part1(input: &[Group]) -> usize {
+    input.iter().map(|group| group.union().len()).sum()
+}
 
-a
-b
-c
+#[aoc(day6, part2)]
+pub fn part2(input: &[Group]) -> usize {
+    input.iter().map(|group| group.intersection().len()).sum()
+}
 
-ab
-ac
+solution!(
+    Day6,
+    6,
+    Vec<Group>,
+    |input| parse_groups(input, &Alphabet::lowercase()),
+    part1,
+    part2
+);
 
-a
-a
-a
-a
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-b";
+    const SAMPLE: &str = include_str!("../examples/day06/sample.txt");
 
     #[test]
     fn sample1() {
@@ -73,4 +299,36 @@ b";
         let input = input_generator(&SAMPLE);
         assert_eq!(part2(&input), 6);
     }
+
+    #[test]
+    fn answer_sets() {
+        let input = input_generator(SAMPLE);
+        let alphabet = Alphabet::lowercase();
+        let group = &input[2];
+        assert_eq!(group.union().to_string(&alphabet), "abc");
+        assert_eq!(group.intersection().to_string(&alphabet), "a");
+        assert_eq!(group.symmetric_difference().to_string(&alphabet), "bc");
+        assert_eq!(group.at_least(2).to_string(&alphabet), "a");
+        assert!(parse_groups("abc\nA", &alphabet).is_err());
+
+        let wide =
+            Alphabet::new((0..200u32).filter_map(|i| std::char::from_u32(0x100 + i))).unwrap();
+        let groups = parse_groups("\u{100}\u{1c7}\n\u{1c7}", &wide).unwrap();
+        assert_eq!(
+            groups[0].members[0].iter().collect::<Vec<_>>(),
+            vec![0, 0xc7]
+        );
+        assert_eq!(
+            groups[0].intersection().iter().collect::<Vec<_>>(),
+            vec![0xc7]
+        );
+        assert_eq!(groups[0].symmetric_difference(), AnswerSet::Small(1));
+        assert!(Alphabet::new("aba".chars()).is_err());
+
+        let json = serde_json::to_string(&input).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Group>>(&json).unwrap(), input);
+        assert!(serde_json::from_str::<AnswerSet>("[0, 25]").is_ok());
+        assert!(serde_json::from_str::<AnswerSet>("[26]").is_err());
+        assert!(serde_json::from_str::<AnswerSet>("[1000000000000]").is_err());
+    }
 }
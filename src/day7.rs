@@ -1,3 +1,4 @@
+use anyhow::{anyhow, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 use petgraph::algo::*;
@@ -10,16 +11,54 @@ use std::collections::HashSet;
 use string_interner::{DefaultSymbol, StringInterner};
 
 use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 thread_local! {
     static BAGS: RefCell<StringInterner> = Default::default();
 }
 
-#[aoc_generator(day7)]
-pub fn input_generator(input: &str) -> DiGraphMap<DefaultSymbol, u8> {
+// The symbols only mean something to this thread's interner, so the rules are
+// serialized as (outer, inner, count) edges by bag name.
+#[derive(Debug, Clone, Default)]
+pub struct Rules(pub DiGraphMap<DefaultSymbol, u8>);
+
+impl Serialize for Rules {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BAGS.with(|f| {
+            let bags = f.borrow();
+            serializer.collect_seq(
+                self.0
+                    .all_edges()
+                    .map(|(outer, inner, n)| (bags.resolve(outer), bags.resolve(inner), n)),
+            )
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Rules {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let edges = Vec::<(String, String, u8)>::deserialize(deserializer)?;
+        BAGS.with(|f| {
+            let mut bags = f.borrow_mut();
+            Ok(Rules(
+                edges
+                    .iter()
+                    .map(|(outer, inner, n)| {
+                        (bags.get_or_intern(outer), bags.get_or_intern(inner), *n)
+                    })
+                    .collect(),
+            ))
+        })
+    }
+}
+
+pub fn parse_rules(input: &str) -> Result<Rules> {
     let mut dag = DiGraphMap::new();
     let re = Regex::new(r"(\d{1,2}) ([a-z]* [a-z]*) bags?").unwrap();
     for line in input.lines() {
         let rule = line.split("contain").collect::<Vec<&str>>();
+        if rule.len() != 2 {
+            return Err(anyhow!("Expected one 'contain' in {:?}", line));
+        }
         let root_bag_tmp = rule[0].replace("bags", "");
         let root_bag = BAGS.with(|f| f.borrow_mut().get_or_intern(root_bag_tmp.as_str().trim()));
 
@@ -37,21 +76,26 @@ pub fn input_generator(input: &str) -> DiGraphMap<DefaultSymbol, u8> {
             //     dag.node_indices().find(|i| dag[*i] == *right_bag).unwrap()
             // };
 
-            dag.add_edge(root_bag, right_bag, amount.parse::<u8>().unwrap());
+            dag.add_edge(root_bag, right_bag, amount.parse::<u8>()?);
         }
     }
-    return dag;
+    Ok(Rules(dag))
+}
+
+#[aoc_generator(day7)]
+pub fn input_generator(input: &str) -> Rules {
+    parse_rules(input).expect("Could not parse the rules")
 }
 
 #[aoc(day7, part1)]
-pub fn part1(input: &DiGraphMap<DefaultSymbol, u8>) -> usize {
-    let graph = Reversed(input);
+pub fn part1(input: &Rules) -> usize {
+    let graph = Reversed(&input.0);
     let gold_node = BAGS.with(|f| f.borrow_mut().get_or_intern("shiny gold"));
     Dfs::new(graph, gold_node).iter(graph).count() - 1
 }
 
 #[aoc(day7, part2)]
-pub fn part2(input: &DiGraphMap<DefaultSymbol, u8>) -> u32 {
+pub fn part2(input: &Rules) -> u32 {
     let gold_node = BAGS.with(|f| f.borrow_mut().get_or_intern("shiny gold"));
     // Dfs::new(input, gold_node)
     //     .iter(input)
@@ -73,30 +117,18 @@ pub fn part2(input: &DiGraphMap<DefaultSymbol, u8>) -> u32 {
             .sum::<u32>()
     }
     // assert_eq!(transitive_children(input, gold_node), bags);
-    transitive_children(input, gold_node)
+    transitive_children(&input.0, gold_node)
 }
 
+solution!(Day7, 7, Rules, parse_rules, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
-dark orange bags contain 3 bright white bags, 4 muted yellow bags.
-bright white bags contain 1 shiny gold bag.
-muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
-shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
-dark olive bags contain 3 faded blue bags, 4 dotted black bags.
-vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
-faded blue bags contain no other bags.
-dotted black bags contain no other bags.";
-
-    const SAMPLE2: &str = "shiny gold bags contain 2 dark red bags.
-dark red bags contain 2 dark orange bags.
-dark orange bags contain 2 dark yellow bags.
-dark yellow bags contain 2 dark green bags.
-dark green bags contain 2 dark blue bags.
-dark blue bags contain 2 dark violet bags.
-dark violet bags contain no other bags.";
+    const SAMPLE: &str = include_str!("../examples/day07/sample.txt");
+
+    const SAMPLE2: &str = include_str!("../examples/day07/nested.txt");
     #[test]
     fn sample1() {
         let input = input_generator(&SAMPLE);
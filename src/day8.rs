@@ -10,12 +10,13 @@ use nom::{
     sequence::{separated_pair, tuple},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashSet, VecDeque},
     str::FromStr,
 };
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Instruction {
     Nop(i16),
     Acc(i16),
@@ -76,15 +77,21 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
     })
 }
 
-#[aoc_generator(day8)]
-pub fn input_generator(input: &str) -> Vec<Instruction> {
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>> {
     input
         .lines()
-        .map(|x| parse_instruction(x))
-        .map(|x| x.unwrap().1)
+        .map(|line| match parse_instruction(line.trim()) {
+            Ok(("", instruction)) => Ok(instruction),
+            _ => Err(anyhow!("Invalid instruction {:?}", line)),
+        })
         .collect()
 }
 
+#[aoc_generator(day8)]
+pub fn input_generator(input: &str) -> Vec<Instruction> {
+    parse_program(input).expect("Could not parse the program")
+}
+
 #[derive(Default)]
 struct Computer {
     instructions: VecDeque<Instruction>,
@@ -163,19 +170,13 @@ pub fn part2(input: &Vec<Instruction>) -> i64 {
     panic!("did not find any");
 }
 
+solution!(Day8, 8, Vec<Instruction>, parse_program, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &str = "nop +0
-acc +1
-jmp +4
-acc +3
-jmp -3
-acc -99
-acc +1
-jmp -4
-acc +6";
+    const SAMPLE: &str = include_str!("../examples/day08/sample.txt");
 
     #[test]
     fn sample1() {
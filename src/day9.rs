@@ -1,59 +1,165 @@
+use crate::solution::Explanation;
+use anyhow::{anyhow, Context, Result};
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub fn parse_numbers(input: &str) -> Result<Vec<u64>> {
+    input
+        .lines()
+        .map(|x| {
+            x.trim()
+                .parse::<u64>()
+                .with_context(|| format!("Invalid number {:?}", x))
+        })
+        .collect()
+}
 
 #[aoc_generator(day9)]
 pub fn input_generator(input: &str) -> Vec<u64> {
-    input.lines().map(|x| x.parse::<u64>().unwrap()).collect()
+    parse_numbers(input).expect("Could not parse the numbers")
 }
 
-fn find_part1(input: &[u64], preamble: usize) -> impl Iterator<Item=u64> + '_ {
-    let mut iter = input.iter().enumerate();
-    for i in 0..preamble {
-        iter.next().ok_or(i);
+#[derive(Debug, Clone)]
+pub struct XmasCipher<'a> {
+    data: &'a [u64],
+    preamble: usize,
+}
+
+impl<'a> XmasCipher<'a> {
+    pub fn new(data: &'a [u64], preamble: usize) -> Self {
+        XmasCipher { data, preamble }
     }
-    iter.filter_map(move |(i, &x)| {
-        for (i2, &y) in input[(i-preamble)..i].iter().enumerate() {
-            for &z in input[(i-preamble)+i2..i].iter() {
-                if y+z==x && z != y {
-                    return None;
+
+    // Positions and values of every number that is not the sum of two
+    // different numbers among the preamble before it.
+    pub fn invalid_numbers(&self) -> Vec<(usize, u64)> {
+        if self.preamble == 0 {
+            return self.data.iter().copied().enumerate().collect();
+        }
+        // Multiset of the sums of all pairs in the current window.
+        let mut sums: HashMap<u64, usize> = HashMap::new();
+        let pairs = |window: &[u64], x: u64| {
+            window
+                .iter()
+                .filter(move |y| **y != x)
+                .map(move |y| x + y)
+                .collect::<Vec<u64>>()
+        };
+        let head = self.preamble.min(self.data.len());
+        for i in 0..head {
+            for sum in pairs(&self.data[..i], self.data[i]) {
+                *sums.entry(sum).or_insert(0) += 1;
+            }
+        }
+
+        let mut invalid = Vec::new();
+        for i in self.preamble..self.data.len() {
+            let x = self.data[i];
+            if sums.get(&x).copied().unwrap_or(0) == 0 {
+                invalid.push((i, x));
+            }
+            let outgoing = self.data[i - self.preamble];
+            let window = &self.data[i - self.preamble + 1..i];
+            for sum in pairs(window, outgoing) {
+                let count = sums.get_mut(&sum).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    sums.remove(&sum);
                 }
             }
+            for sum in pairs(window, x) {
+                *sums.entry(sum).or_insert(0) += 1;
+            }
         }
-        return Some(x)
-    })
-}
+        invalid
+    }
 
-fn find_part2(input: &[u64], preamble: usize) -> Vec<u64> {
-    let target = find_part1(input, preamble).next().unwrap();
-    input.iter().enumerate().filter_map(move |(i, _)| {
-        let mut stack = Vec::new();
-        let mut acc = 0;
-        for x in input[i..input.len()].iter() {
-            acc += x;
-            stack.push(*x);
-            if acc == target {
-                return Some(stack);
+    pub fn first_invalid(&self) -> Option<(usize, u64)> {
+        self.invalid_numbers().into_iter().next()
+    }
+
+    // Contiguous range of at least two numbers summing up to target.
+    pub fn find_range(&self, target: u64) -> Option<Range<usize>> {
+        let mut start = 0;
+        let mut sum = 0;
+        for (end, x) in self.data.iter().enumerate() {
+            sum += x;
+            while sum > target {
+                sum -= self.data[start];
+                start += 1;
             }
-            if acc >= target {
-                return None
+            if sum == target && end > start {
+                return Some(start..end + 1);
             }
         }
-        return None
-    }).next().unwrap()
+        None
+    }
 
+    pub fn weakness_range(&self) -> Result<Range<usize>> {
+        let (_, target) = self
+            .first_invalid()
+            .ok_or_else(|| anyhow!("Every number is valid"))?;
+        self.find_range(target)
+            .ok_or_else(|| anyhow!("No contiguous range sums up to {}", target))
+    }
+
+    pub fn weakness(&self) -> Result<u64> {
+        let range = &self.data[self.weakness_range()?];
+        Ok(range.iter().min().unwrap() + range.iter().max().unwrap())
+    }
+}
+
+fn find_part1(input: &[u64], preamble: usize) -> impl Iterator<Item = u64> {
+    XmasCipher::new(input, preamble)
+        .invalid_numbers()
+        .into_iter()
+        .map(|(_, x)| x)
+}
+
+#[cfg(test)]
+fn find_part2(input: &[u64], preamble: usize) -> Vec<u64> {
+    let range = XmasCipher::new(input, preamble)
+        .weakness_range()
+        .expect("No weakness found");
+    input[range].to_vec()
 }
 
 #[aoc(day9, part1)]
 pub fn part1(input: &[u64]) -> u64 {
-    find_part1(input, 25).next().unwrap()
+    find_part1(input, 25).next().expect("Every number is valid")
 }
 
 #[aoc(day9, part2)]
 pub fn part2(input: &[u64]) -> u64 {
-    let range = find_part2(input, 25);
-    range.iter().min().unwrap() + range.iter().max().unwrap()
+    XmasCipher::new(input, 25)
+        .weakness()
+        .expect("No weakness found")
+}
+
+fn explain(part: u32, input: &[u64]) -> Option<Explanation> {
+    if part != 2 {
+        return None;
+    }
+    let range = XmasCipher::new(input, 25).weakness_range().ok()?;
+    Some(Explanation::Range {
+        start: range.start,
+        end: range.end,
+        values: input[range].to_vec(),
+    })
 }
 
+solution!(
+    Day9,
+    9,
+    Vec<u64>,
+    parse_numbers,
+    part1,
+    part2,
+    explain = explain
+);
+
 #[cfg(test)]
 mod tests {
     use lazy_static::lazy_static;
@@ -95,4 +201,22 @@ mod tests {
         let input = input_generator(&SAMPLE2);
         assert_eq!(find_part2(&input, 5), vec![15,25,47,40]);
     }
+
+    #[test]
+    fn cipher() {
+        let input = input_generator(SAMPLE2);
+        let cipher = XmasCipher::new(&input, 5);
+        assert_eq!(cipher.invalid_numbers(), vec![(14, 127)]);
+        assert_eq!(cipher.weakness_range().unwrap(), 2..6);
+        assert_eq!(cipher.weakness().unwrap(), 62);
+
+        let valid = XmasCipher::new(&input[..14], 5);
+        assert!(valid.invalid_numbers().is_empty());
+        assert!(valid.weakness().is_err());
+        assert_eq!(XmasCipher::new(&[1, 2, 4], 1).find_range(100), None);
+
+        // 10 would need 5 twice, 3 needs the 1 that already left the window.
+        let invalid = XmasCipher::new(&[1, 2, 3, 4, 5, 10, 3], 5).invalid_numbers();
+        assert_eq!(invalid, vec![(5, 10), (6, 3)]);
+    }
 }
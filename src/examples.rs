@@ -0,0 +1,208 @@
+use crate::crosscheck::run_solver;
+use crate::registry;
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DIR: &str = "examples";
+pub const MANIFEST: &str = "answers.toml";
+
+// One examples/dayNN/<name>.txt with the answers listed for it under
+// `[<name>]` in examples/dayNN/answers.toml. Parts without an entry are not
+// checked, e.g. when the example only fits one of them. Parts listed in
+// `ignore = ["part2"]` are too slow for every test run and are only checked
+// by `cargo test --release -- --ignored`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub day: u32,
+    pub name: String,
+    pub input: String,
+    pub expected: BTreeMap<u32, String>,
+    pub ignored: BTreeSet<u32>,
+}
+
+#[derive(Debug, Default)]
+struct Answers {
+    expected: BTreeMap<u32, String>,
+    ignored: BTreeSet<u32>,
+}
+
+pub fn day_dir<P: AsRef<Path>>(dir: P, day: u32) -> PathBuf {
+    dir.as_ref().join(format!("day{:02}", day))
+}
+
+fn parse_part(name: &str, key: &str) -> Result<u32> {
+    key.strip_prefix("part")
+        .and_then(|part| part.parse().ok())
+        .ok_or_else(|| anyhow!("[{}]: expected partN, found {:?}", name, key))
+}
+
+fn parse_manifest(content: &str) -> Result<BTreeMap<String, Answers>> {
+    let manifest: BTreeMap<String, BTreeMap<String, toml::Value>> = toml::from_str(content)?;
+    manifest
+        .into_iter()
+        .map(|(name, entries)| {
+            let mut answers = Answers::default();
+            for (key, value) in entries {
+                match (key.as_str(), value) {
+                    ("ignore", toml::Value::Array(parts)) => {
+                        for part in parts {
+                            let part = part.as_str().ok_or_else(|| {
+                                anyhow!("[{}] ignore: expected a list of partN", name)
+                            })?;
+                            answers.ignored.insert(parse_part(&name, part)?);
+                        }
+                    }
+                    (_, toml::Value::String(s)) => {
+                        answers.expected.insert(parse_part(&name, &key)?, s);
+                    }
+                    (_, toml::Value::Integer(n)) => {
+                        answers
+                            .expected
+                            .insert(parse_part(&name, &key)?, n.to_string());
+                    }
+                    (_, other) => {
+                        return Err(anyhow!("[{}] {}: invalid answer {}", name, key, other))
+                    }
+                }
+            }
+            if let Some(part) = answers
+                .ignored
+                .iter()
+                .find(|part| !answers.expected.contains_key(part))
+            {
+                return Err(anyhow!(
+                    "[{}] ignores part{}, which has no answer",
+                    name,
+                    part
+                ));
+            }
+            Ok((name, answers))
+        })
+        .collect()
+}
+
+// Every example of a day, sorted by name. A day without a directory has none.
+pub fn load<P: AsRef<Path>>(dir: P, day: u32) -> Result<Vec<Example>> {
+    let dir = day_dir(dir, day);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let manifest_path = dir.join(MANIFEST);
+    let mut manifest = match fs::read_to_string(&manifest_path) {
+        Ok(content) => parse_manifest(&content)
+            .with_context(|| format!("Invalid manifest {}", manifest_path.display()))?,
+        Err(_) => BTreeMap::new(),
+    };
+
+    let mut examples = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension() != Some(OsStr::new("txt")) {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("Invalid example name {}", path.display()))?
+            .to_owned();
+        let answers = manifest.remove(&name).unwrap_or_default();
+        examples.push(Example {
+            day,
+            expected: answers.expected,
+            ignored: answers.ignored,
+            input: fs::read_to_string(&path)?,
+            name,
+        });
+    }
+    if let Some(name) = manifest.keys().next() {
+        return Err(anyhow!(
+            "{} lists {:?} but there is no {}.txt",
+            manifest_path.display(),
+            name,
+            name
+        ));
+    }
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(examples)
+}
+
+impl Example {
+    // Runs every registered solver of each expected part, returning what went
+    // wrong. Checks the ignored parts instead with `ignored`.
+    pub fn check(&self, ignored: bool) -> Vec<String> {
+        let mut failures = Vec::new();
+        for (part, expected) in self
+            .expected
+            .iter()
+            .filter(|(part, _)| self.ignored.contains(part) == ignored)
+        {
+            let mut solvers = registry::for_day(self.day)
+                .filter(|s| s.part == *part)
+                .peekable();
+            if solvers.peek().is_none() {
+                failures.push(format!("day{} has no part{}", self.day, part));
+            }
+            for solver in solvers {
+                match run_solver(solver, &self.input).answer {
+                    Ok(answer) if answer == *expected => {}
+                    Ok(answer) => failures.push(format!(
+                        "{} on {}: expected {}, got {}",
+                        solver, self.name, expected, answer
+                    )),
+                    Err(err) => failures.push(format!("{} on {}: {}", solver, self.name, err)),
+                }
+            }
+        }
+        failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // With `crlf`, the example is fed as if saved on Windows: with a BOM, CRLF
+    // line endings and a trailing blank line.
+    fn check_example(day: u32, name: &str, crlf: bool, ignored: bool) {
+        let examples = load(Path::new(env!("CARGO_MANIFEST_DIR")).join(DIR), day).unwrap();
+        let mut example = examples.into_iter().find(|e| e.name == name).unwrap();
+        if crlf {
+            example.input = format!("\u{feff}{}\r\n", example.input.replace('\n', "\r\n"));
+        }
+        let failures = example.check(ignored);
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/example_tests.rs"));
+
+    #[test]
+    fn manifest() {
+        let manifest =
+            parse_manifest("[sample]\npart1 = 514579\npart2 = \"abc\"\nignore = [\"part2\"]\n")
+                .unwrap();
+        assert_eq!(manifest["sample"].expected[&1], "514579");
+        assert_eq!(manifest["sample"].expected[&2], "abc");
+        assert_eq!(manifest["sample"].ignored, vec![2].into_iter().collect());
+        assert!(parse_manifest("[sample]\nthree = 1\n").is_err());
+        assert!(parse_manifest("[sample]\npart1 = 1.5\n").is_err());
+        assert!(parse_manifest("[sample]\npart1 = 1\nignore = [\"part2\"]\n").is_err());
+
+        let example = Example {
+            day: 1,
+            name: "sample".to_owned(),
+            input: "1721\n979\n366\n299\n675\n1456\n".to_owned(),
+            expected: vec![(1, "1".to_owned()), (3, "2".to_owned())]
+                .into_iter()
+                .collect(),
+            ignored: vec![3].into_iter().collect(),
+        };
+        assert_eq!(
+            example.check(false),
+            vec!["day1 part1 (Chars) on sample: expected 1, got 514579"]
+        );
+        assert_eq!(example.check(true), vec!["day1 has no part3"]);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::answers::sha256;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const YEAR: u32 = 2020;
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const CONFIG_PATH: &str = "aoc.toml";
+pub const CACHE_DIR: &str = "input/cache";
+pub(crate) const USER_AGENT: &str = "advent_of_code_2020 input fetcher";
+
+// Settings from aoc.toml, overridden by $AOC_SESSION, $AOC_BASE_URL,
+// $AOC_USER and $AOC_CACHE_DIR. Both aoc.toml and the cache are ignored by
+// git, the session token must never be committed.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub session: Option<String>,
+    pub base_url: Option<String>,
+    pub user: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    pub fn load() -> Result<Self> {
+        let mut config = Config::from_file(CONFIG_PATH)?;
+        let var = |name| env::var(name).ok().filter(|v: &String| !v.is_empty());
+        if let Some(session) = var("AOC_SESSION") {
+            config.session = Some(session);
+        }
+        if let Some(base_url) = var("AOC_BASE_URL") {
+            config.base_url = Some(base_url);
+        }
+        if let Some(user) = var("AOC_USER") {
+            config.user = Some(user);
+        }
+        if let Some(cache_dir) = var("AOC_CACHE_DIR") {
+            config.cache_dir = Some(cache_dir.into());
+        }
+        Ok(config)
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .unwrap_or(DEFAULT_BASE_URL)
+            .trim_end_matches('/')
+    }
+
+    pub(crate) fn session(&self) -> Result<&str> {
+        self.session.as_deref().ok_or_else(|| {
+            anyhow!(
+                "No session token, set $AOC_SESSION or session in {}",
+                CONFIG_PATH
+            )
+        })
+    }
+
+    // Inputs differ per account. Without a configured user name the cache is
+    // keyed by a hash of the session token, so the token itself never ends up
+    // in a path.
+    pub fn user_key(&self) -> Option<String> {
+        match (&self.user, &self.session) {
+            (Some(user), _) => Some(user.clone()),
+            (None, Some(session)) => Some(sha256(session)[..12].to_owned()),
+            (None, None) => None,
+        }
+    }
+
+    pub fn cache_path(&self, day: u32) -> Option<PathBuf> {
+        let dir = self
+            .cache_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(CACHE_DIR));
+        Some(
+            dir.join(self.user_key()?)
+                .join(YEAR.to_string())
+                .join(format!("day{}.txt", day)),
+        )
+    }
+
+    pub fn input_url(&self, day: u32) -> String {
+        format!("{}/{}/day/{}/input", self.base_url(), YEAR, day)
+    }
+}
+
+pub fn download(config: &Config, day: u32) -> Result<String> {
+    let url = config.input_url(day);
+    let response = ureq::get(&url)
+        .set("Cookie", &format!("session={}", config.session()?))
+        .set("User-Agent", USER_AGENT)
+        .call();
+    match response {
+        Ok(response) => Ok(response.into_string()?),
+        Err(ureq::Error::Status(404, _)) => Err(anyhow!("day{} is not unlocked yet", day)),
+        Err(ureq::Error::Status(code, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            Err(anyhow!(
+                "{} answered {}: {}",
+                url,
+                code,
+                body.lines().next().unwrap_or("")
+            ))
+        }
+        Err(err) => Err(err).with_context(|| format!("Could not fetch {}", url)),
+    }
+}
+
+// Returns the cached input, downloading it first if this user has none yet.
+// The second value tells whether it was downloaded.
+pub fn fetch(config: &Config, day: u32) -> Result<(PathBuf, bool)> {
+    let path = config.cache_path(day).ok_or_else(|| {
+        anyhow!(
+            "No session token, set $AOC_SESSION or session in {}",
+            CONFIG_PATH
+        )
+    })?;
+    if path.is_file() {
+        return Ok((path, false));
+    }
+    let input = download(config, day)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, input)?;
+    Ok((path, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    #[test]
+    fn fetch_and_cache() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/2020/day/1/input" if request.header("Cookie") == Some("session=abc") => {
+                (200, "1721\n979\n".to_owned())
+            }
+            "/2020/day/1/input" => (400, "Puzzle inputs differ by user.\n".to_owned()),
+            _ => (404, "Not Found".to_owned()),
+        });
+        let cache_dir = env::temp_dir().join(format!("aoc-fetch-{}", std::process::id()));
+        let config = Config {
+            session: Some("abc".to_owned()),
+            base_url: Some(format!("{}/", server.url)),
+            user: None,
+            cache_dir: Some(cache_dir.clone()),
+        };
+
+        let (path, downloaded) = fetch(&config, 1).unwrap();
+        assert!(downloaded);
+        assert!(path.starts_with(cache_dir.join(config.user_key().unwrap())));
+        assert!(path.ends_with("2020/day1.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1721\n979\n");
+        assert_eq!(fetch(&config, 1).unwrap(), (path, false));
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(server.requests()[0].method, "GET");
+        assert!(server.requests()[0].header("User-Agent").is_some());
+
+        let error = fetch(&config, 2).unwrap_err().to_string();
+        assert_eq!(error, "day2 is not unlocked yet");
+        let other = Config {
+            session: Some("wrong".to_owned()),
+            user: Some("someone".to_owned()),
+            ..config.clone()
+        };
+        assert!(fetch(&other, 1)
+            .unwrap_err()
+            .to_string()
+            .ends_with("answered 400: Puzzle inputs differ by user."));
+        assert!(fetch(&Config::default(), 1).is_err());
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn config() {
+        let config: Config =
+            toml::from_str("session = \"abc\"\nbase_url = \"http://localhost:1/\"\n").unwrap();
+        assert_eq!(config.input_url(5), "http://localhost:1/2020/day/5/input");
+        assert_eq!(config.user_key().unwrap().len(), 12);
+        assert_eq!(Config::default().base_url(), DEFAULT_BASE_URL);
+        assert!(toml::from_str::<Config>("token = \"abc\"").is_err());
+    }
+}
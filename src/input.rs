@@ -0,0 +1,126 @@
+use anyhow::{anyhow, Result};
+use array2d::Array2D;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+// Inputs saved on Windows or pasted from a browser come with a BOM, CRLF line
+// endings or trailing whitespace. registry::generate and solution::parse
+// normalize every input before it reaches a generator, so the generators only
+// ever see `\n` and no trailing blank lines. Calling a dayN::input_generator
+// directly skips this; normalize the input first.
+pub fn normalize(input: &str) -> String {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let lines: Vec<&str> = input.lines().map(|line| line.trim_end()).collect();
+    lines.join("\n").trim_end_matches('\n').to_owned()
+}
+
+// The blocks of lines separated by blank lines, without the separators. Blank
+// lines may hold whitespace and end in CRLF, so this also works on inputs that
+// were not normalized.
+pub fn blocks(input: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let content = line.trim_end();
+        if content.is_empty() {
+            if let Some(start) = start.take() {
+                blocks.push(&input[start..end]);
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + content.len();
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        blocks.push(&input[start..end]);
+    }
+    blocks
+}
+
+// The parsed input of the days that read a grid. It is serialized as its rows
+// and checked like a parsed one when read back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    into = "Vec<Vec<T>>",
+    try_from = "Vec<Vec<T>>",
+    bound(
+        serialize = "T: Clone + Serialize",
+        deserialize = "T: Clone + Deserialize<'de>"
+    )
+)]
+pub struct Grid<T: Clone>(pub Array2D<T>);
+
+impl<T: Clone> Grid<T> {
+    pub fn from_rows(rows: &[Vec<T>]) -> Result<Self> {
+        if rows.is_empty() || rows[0].is_empty() {
+            return Err(anyhow!("The grid is empty"));
+        }
+        if rows.iter().any(|row| row.len() != rows[0].len()) {
+            return Err(anyhow!("The rows differ in length"));
+        }
+        Ok(Grid(Array2D::from_rows(rows)))
+    }
+}
+
+impl<T: Clone> Deref for Grid<T> {
+    type Target = Array2D<T>;
+
+    fn deref(&self) -> &Array2D<T> {
+        &self.0
+    }
+}
+
+impl<T: Clone> TryFrom<Vec<Vec<T>>> for Grid<T> {
+    type Error = anyhow::Error;
+
+    fn try_from(rows: Vec<Vec<T>>) -> Result<Self> {
+        Grid::from_rows(&rows)
+    }
+}
+
+impl<T: Clone> From<Grid<T>> for Vec<Vec<T>> {
+    fn from(grid: Grid<T>) -> Self {
+        grid.0.as_rows()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_input() {
+        assert_eq!(normalize("\u{feff}1721\r\n979  \r\n\r\n\n"), "1721\n979");
+        assert_eq!(normalize("a\n\nb\n"), "a\n\nb");
+        assert_eq!(normalize("  indented\n"), "  indented");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn split_blocks() {
+        assert_eq!(
+            blocks("abc\n\na\nb\nc\n\nab\nac"),
+            vec!["abc", "a\nb\nc", "ab\nac"]
+        );
+        assert_eq!(
+            blocks("\r\nabc\r\n \r\n\r\na\r\nb \r\n"),
+            vec!["abc", "a\r\nb"]
+        );
+        assert!(blocks("\n\n").is_empty());
+    }
+
+    #[test]
+    fn grid() {
+        let grid = Grid::from_rows(&[vec![1, 2], vec![3, 4]]).unwrap();
+        assert_eq!(grid[(1, 0)], 3);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json, "[[1,2],[3,4]]");
+        assert_eq!(serde_json::from_str::<Grid<u8>>(&json).unwrap(), grid);
+        assert!(serde_json::from_str::<Grid<u8>>("[[1,2],[3]]").is_err());
+        assert!(serde_json::from_str::<Grid<u8>>("[]").is_err());
+    }
+}
@@ -1,5 +1,8 @@
 use aoc_runner_derive::aoc_lib;
 
+#[macro_use]
+pub mod solution;
+
 pub mod day1;
 pub mod day2;
 pub mod day3;
@@ -18,5 +21,21 @@ pub mod day15;
 pub mod day16;
 pub mod day17;
 pub mod day18;
+pub mod day19;
+pub mod day20;
+
+pub mod answers;
+pub mod baseline;
+pub mod crosscheck;
+pub mod examples;
+pub mod fetch;
+pub mod input;
+pub mod matrix;
+pub mod registry;
+pub mod scaffold;
+pub mod submit;
+
+#[cfg(test)]
+mod mock_server;
 
 aoc_lib! { year = 2020 }
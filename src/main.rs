@@ -1,3 +1,678 @@
-use aoc_runner_derive::aoc_main;
+use advent_of_code_2020::answers::{self, AnswerStore, Verdict};
+use advent_of_code_2020::baseline::format_duration;
+use advent_of_code_2020::crosscheck::run_solver;
+use advent_of_code_2020::day12::{self, Action, Direct, MovementModel, Navigator, Waypoint};
+use advent_of_code_2020::day4::{self, PassportSchema};
+use advent_of_code_2020::matrix::{self, Matrix};
+use advent_of_code_2020::registry::RegisteredSolver;
+use advent_of_code_2020::solution::{self, Explanation};
+use advent_of_code_2020::submit::{self, Outcome, Submission, Submissions};
+use advent_of_code_2020::{day1, fetch, input, registry, scaffold};
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+use std::time::{Duration, Instant};
 
-aoc_main! { lib = advent_of_code_2020 }
+const USAGE: &str = "Usage:
+    advent_of_code_2020 run <day> [--part N] [--variant NAME] [--input PATH|-]
+    advent_of_code_2020 run --all
+    advent_of_code_2020 run <day>|--all --inputs DIR
+    advent_of_code_2020 run <day> --dump-parsed json [--input PATH|-]
+    advent_of_code_2020 list
+    advent_of_code_2020 new <day>
+    advent_of_code_2020 fetch <day>
+    advent_of_code_2020 submit <day> <part> [--variant NAME] [--input PATH|-]
+    advent_of_code_2020 passports [--format jsonl|csv] [--presence] [--input PATH|-]
+    advent_of_code_2020 plot 12 [--part N] [--svg FILE] [--input PATH|-]
+
+Options for run:
+    --part N          only run part N
+    --variant NAME    only run the solver registered as NAME
+    --input PATH|-    read the input from PATH or stdin (default: input/2020/dayN.txt)
+    --inputs DIR      run on every DIR/dayNN/<name>.txt and print a matrix of answers
+                      checked against DIR/dayNN/answers.toml, like examples/
+    --answers PATH    answer store to check against (default: answers.toml)
+    --record          save answers the store does not know yet
+    --explain         show how the answer came about, for days that support it
+    --dump-parsed json print the generator's output as JSON instead of solving
+    --input-format json
+                      the input is parsed JSON as printed by --dump-parsed
+    --target N, --k N day 1 only: find k entries summing to N (default: 2020, 2)
+
+Exits with 1 if a solver fails or contradicts the answer store.
+
+new creates src/dayN.rs from the usual template, registers it in src/lib.rs
+and adds an empty example under examples/dayNN/.
+
+fetch downloads the input with the session token from $AOC_SESSION or
+aoc.toml (session = \"...\", optionally base_url and user) into
+input/cache/<user>/2020/dayN.txt, which run reads when input/2020/dayN.txt
+does not exist. Cached inputs are never downloaded again.
+
+submit runs the solver of one part and posts its answer, logging every attempt
+in input/submissions.toml. Answers already rejected, beyond a recorded \"too
+high\" or \"too low\", or sent while rate limited are refused without asking
+the server. Correct answers are recorded in answers.toml.
+
+passports prints day 4's passports as JSON Lines (default) or CSV, with the
+height in cm and the failed rules of the strict schema, or of the presence
+schema with --presence. Passports that do not parse get an error column.
+
+plot draws day 12's route in the terminal, with the ship moving itself (part
+1, default) or following the waypoint (part 2), and saves it as SVG with --svg.";
+
+struct Row {
+    day: u32,
+    part: String,
+    variant: String,
+    generate_time: Option<Duration>,
+    solve_time: Duration,
+    answer: Result<String, String>,
+    verdict: Option<Verdict>,
+    explanation: Option<Explanation>,
+}
+
+impl Row {
+    fn is_ok(&self) -> bool {
+        self.answer.is_ok() && !matches!(self.verdict, Some(Verdict::Wrong { .. }))
+    }
+}
+
+#[derive(Default)]
+struct RunOptions {
+    day: Option<u32>,
+    all: bool,
+    part: Option<u32>,
+    variant: Option<String>,
+    input: Option<String>,
+    inputs: Option<String>,
+    answers: Option<String>,
+    record: bool,
+    explain: bool,
+    dump_parsed: bool,
+    parsed_input: bool,
+    target: Option<i64>,
+    k: Option<usize>,
+}
+
+// JSON is the only format parsed inputs are dumped in, for now.
+fn json_format(format: &str) -> Result<bool> {
+    match format {
+        "json" => Ok(true),
+        other => Err(anyhow!("Unknown format {:?}, expected json", other)),
+    }
+}
+
+fn parse_day(s: &str) -> Result<u32> {
+    s.trim_start_matches("day")
+        .parse()
+        .with_context(|| format!("Invalid day {:?}", s))
+}
+
+fn parse_run_options(args: &[String]) -> Result<RunOptions> {
+    let mut options = RunOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--all" => options.all = true,
+            "--part" | "-p" => options.part = Some(value()?.parse().context("Invalid part")?),
+            "--variant" | "-v" => options.variant = Some(value()?.clone()),
+            "--input" | "-i" => options.input = Some(value()?.clone()),
+            "--inputs" => options.inputs = Some(value()?.clone()),
+            "--answers" => options.answers = Some(value()?.clone()),
+            "--record" => options.record = true,
+            "--explain" => options.explain = true,
+            "--target" => options.target = Some(value()?.parse().context("Invalid target")?),
+            "--dump-parsed" => options.dump_parsed = json_format(value()?)?,
+            "--input-format" => options.parsed_input = json_format(value()?)?,
+            "--k" | "-k" => options.k = Some(value()?.parse().context("Invalid k")?),
+            day if options.day.is_none() && !day.starts_with('-') => {
+                options.day = Some(parse_day(day)?)
+            }
+            other => return Err(anyhow!("Unexpected argument {:?}", other)),
+        }
+    }
+    match (options.day, options.all) {
+        (None, false) => Err(anyhow!("Missing day")),
+        (Some(_), true) => Err(anyhow!("Pass either a day or --all")),
+        (None, true) if options.input.is_some() => {
+            Err(anyhow!("--input needs a single day, not --all"))
+        }
+        (None, true) if options.dump_parsed || options.parsed_input => Err(anyhow!(
+            "--dump-parsed and --input-format need a single day, not --all"
+        )),
+        _ if (options.dump_parsed || options.parsed_input)
+            && (options.inputs.is_some()
+                || options.explain
+                || options.target.is_some()
+                || options.k.is_some()) =>
+        {
+            Err(anyhow!(
+                "--dump-parsed and --input-format do not combine with --inputs, --explain, --target or --k"
+            ))
+        }
+        _ if options.inputs.is_some()
+            && (options.input.is_some()
+                || options.explain
+                || options.target.is_some()
+                || options.k.is_some()) =>
+        {
+            Err(anyhow!(
+                "--inputs does not combine with --input, --explain, --target or --k"
+            ))
+        }
+        _ => Ok(options),
+    }
+}
+
+// `-` reads stdin, a missing path falls back to aoc-runner's input location.
+fn read_input(day: u32, path: Option<&str>) -> Result<String> {
+    match path {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        Some(path) => fs::read_to_string(path).with_context(|| format!("Could not read {}", path)),
+        None => {
+            let path = registry::input_path(day);
+            fs::read_to_string(&path).with_context(|| format!("Could not read {}", path.display()))
+        }
+    }
+}
+
+fn k_sum_row(input: &str, target: i64, k: usize) -> Row {
+    let start = Instant::now();
+    let entries = day1::parse_entries(input);
+    let generate_time = start.elapsed();
+    let start = Instant::now();
+    let indices = day1::find_k_sum(&entries, target, k);
+    let solve_time = start.elapsed();
+    let answer = indices
+        .as_ref()
+        .map(|indices| {
+            indices
+                .iter()
+                .map(|i| entries[*i])
+                .product::<i64>()
+                .to_string()
+        })
+        .ok_or_else(|| format!("No {} entries sum to {}", k, target));
+    Row {
+        day: 1,
+        part: "-".to_owned(),
+        variant: format!("k={} target={}", k, target),
+        generate_time: Some(generate_time),
+        solve_time,
+        answer,
+        verdict: None,
+        explanation: indices
+            .map(|indices| Explanation::Entries(indices.iter().map(|i| entries[*i]).collect())),
+    }
+}
+
+fn matching_solvers(day: u32, options: &RunOptions) -> Result<Vec<&'static RegisteredSolver>> {
+    let solvers: Vec<_> = registry::for_day(day)
+        .filter(|s| options.part.unwrap_or(s.part) == s.part)
+        .filter(|s| match &options.variant {
+            Some(variant) => s.is_variant(Some(variant)),
+            None => true,
+        })
+        .collect();
+    if solvers.is_empty() {
+        return Err(anyhow!("No solver of day{} matches", day));
+    }
+    Ok(solvers)
+}
+
+fn solver_rows(
+    day: u32,
+    input: &str,
+    options: &RunOptions,
+    store: &mut AnswerStore,
+) -> Result<Vec<Row>> {
+    let solvers = matching_solvers(day, options)?;
+
+    let hash = answers::input_hash(input);
+    let outcomes: Vec<_> = solvers.iter().map(|s| run_solver(s, input)).collect();
+    // --record only keeps a part's answer when all of its solvers agree on it.
+    let agreed: Vec<u32> = outcomes
+        .iter()
+        .map(|o| o.solver.part)
+        .filter(|part| {
+            let mut answers = outcomes.iter().filter(|o| o.solver.part == *part);
+            let first = answers.next().map(|o| &o.answer);
+            matches!(first, Some(Ok(_))) && answers.all(|o| Some(&o.answer) == first)
+        })
+        .collect();
+    let mut rows = Vec::new();
+    let mut explained = Vec::new();
+    for outcome in outcomes {
+        let solver = outcome.solver;
+        // Variants of a part explain the same answer, show it once.
+        let explanation =
+            if options.explain && outcome.answer.is_ok() && !explained.contains(&solver.part) {
+                explained.push(solver.part);
+                solution::explain(day, solver.part, input).unwrap_or(None)
+            } else {
+                None
+            };
+        let verdict = outcome.answer.as_ref().ok().map(|answer| {
+            let verdict = store.check(day, solver.part, &hash, answer);
+            if verdict == Verdict::Unknown && options.record && agreed.contains(&solver.part) {
+                store.record(day, solver.part, &hash, answer);
+            }
+            verdict
+        });
+        rows.push(Row {
+            day,
+            part: solver.part.to_string(),
+            variant: solver.variant.unwrap_or("-").to_owned(),
+            // Solvers without a generator parse inside the solver itself.
+            generate_time: solver.generator.map(|_| outcome.generate_time),
+            solve_time: outcome.solve_time,
+            answer: outcome.answer,
+            verdict,
+            explanation,
+        });
+    }
+    Ok(rows)
+}
+
+// Parsed input only goes through the `Solution` types, as the `#[aoc]`
+// runners always start from text. Its hash is not the text's, so there is no
+// verdict either.
+fn parsed_rows(day: u32, json: &str, options: &RunOptions) -> Result<Vec<Row>> {
+    let mut rows = Vec::new();
+    for solver in matching_solvers(day, options)? {
+        let start = Instant::now();
+        let answer = solution::solve_parsed(day, solver.part, solver.variant, json);
+        rows.push(Row {
+            day,
+            part: solver.part.to_string(),
+            variant: solver.variant.unwrap_or("-").to_owned(),
+            generate_time: None,
+            solve_time: start.elapsed(),
+            answer: answer
+                .map(|answer| answer.to_string())
+                .map_err(|err| format!("{:#}", err)),
+            verdict: None,
+            explanation: None,
+        });
+    }
+    Ok(rows)
+}
+
+fn print_table(rows: &[Row]) {
+    let variant_width = rows
+        .iter()
+        .map(|r| r.variant.len())
+        .max()
+        .unwrap_or(0)
+        .max("variant".len());
+    println!(
+        "{:>4}  {:>4}  {:<width$}  {:>10}  {:>10}  answer",
+        "day",
+        "part",
+        "variant",
+        "generator",
+        "solver",
+        width = variant_width
+    );
+    for row in rows {
+        let answer = match (&row.answer, &row.verdict) {
+            (Ok(answer), Some(verdict)) => format!("{} {}", answer, verdict),
+            (Ok(answer), None) => answer.clone(),
+            (Err(err), _) => format!("error: {}", err),
+        };
+        println!(
+            "{:>4}  {:>4}  {:<width$}  {:>10}  {:>10}  {}",
+            row.day,
+            row.part,
+            row.variant,
+            row.generate_time
+                .map_or("-".to_owned(), |t| format_duration(t.as_nanos() as f64)),
+            format_duration(row.solve_time.as_nanos() as f64),
+            answer,
+            width = variant_width
+        );
+        if let Some(explanation) = &row.explanation {
+            for line in explanation.to_string().lines() {
+                println!("{:>12}{}", "", line);
+            }
+        }
+    }
+}
+
+// One matrix per day with named inputs in `dir`, failing if any solver fails,
+// contradicts the store or disagrees with the part's default solver.
+fn run_matrices(dir: &str, options: &RunOptions, store: &mut AnswerStore) -> Result<bool> {
+    let days = match options.day {
+        Some(day) => vec![day],
+        None => registry::days(),
+    };
+    let mut failures = Vec::new();
+    for day in days {
+        let inputs = matrix::load_inputs(dir, day)?;
+        if inputs.is_empty() {
+            if options.day.is_some() {
+                return Err(anyhow!("No inputs for day{} in {}", day, dir));
+            }
+            continue;
+        }
+        let mut solvers = matching_solvers(day, options)?;
+        solvers.sort_by_key(|s| (s.part, s.variant.is_some()));
+        let matrix = Matrix::run(day, &solvers, &inputs, store);
+        println!("{}", matrix);
+        if options.record {
+            matrix.record(store);
+        }
+        failures.extend(matrix.failures());
+    }
+    for failure in failures.iter() {
+        println!("FAILED {}", failure);
+    }
+    Ok(failures.is_empty())
+}
+
+fn run(args: &[String]) -> Result<bool> {
+    let options = parse_run_options(args)?;
+    let answers_path = options
+        .answers
+        .clone()
+        .unwrap_or_else(|| answers::DEFAULT_PATH.to_owned());
+    let mut store = AnswerStore::load(&answers_path)?;
+    let stored = store.answers.len();
+
+    let ok = match &options.inputs {
+        Some(dir) => run_matrices(dir, &options, &mut store)?,
+        None => run_rows(&options, &mut store)?,
+    };
+
+    if store.answers.len() > stored {
+        store.save(&answers_path)?;
+        println!(
+            "Recorded {} new answers in {}",
+            store.answers.len() - stored,
+            answers_path
+        );
+    }
+    Ok(ok)
+}
+
+fn run_rows(options: &RunOptions, store: &mut AnswerStore) -> Result<bool> {
+    let mut rows = Vec::new();
+    match options.day {
+        Some(day) => {
+            if registry::for_day(day).next().is_none() {
+                return Err(anyhow!("No solvers registered for day{}", day));
+            }
+            let input = read_input(day, options.input.as_deref())?;
+            if options.dump_parsed {
+                println!("{}", solution::dump_parsed(day, &input)?);
+                return Ok(true);
+            }
+            if options.parsed_input {
+                rows.extend(parsed_rows(day, &input, options)?);
+            } else if options.target.is_some() || options.k.is_some() {
+                if day != 1 {
+                    return Err(anyhow!("--target and --k only apply to day 1"));
+                }
+                rows.push(k_sum_row(
+                    &input,
+                    options.target.unwrap_or(day1::TARGET),
+                    options.k.unwrap_or(2),
+                ));
+            } else {
+                rows.extend(solver_rows(day, &input, options, store)?);
+            }
+        }
+        None => {
+            for day in registry::days() {
+                let path = registry::input_path(day);
+                match fs::read_to_string(&path) {
+                    Ok(input) => rows.extend(solver_rows(day, &input, options, store)?),
+                    Err(_) => eprintln!("Skipping day{}: no input at {}", day, path.display()),
+                }
+            }
+        }
+    }
+    print_table(&rows);
+    Ok(rows.iter().all(Row::is_ok))
+}
+
+fn list() {
+    for day in registry::days() {
+        let mut solvers = registry::for_day(day).peekable();
+        let first = solvers.peek().unwrap();
+        match first.generator {
+            Some(generator) => println!("day{}  generator {}::{}", day, first.module, generator),
+            None => println!("day{}", day),
+        }
+        for solver in solvers {
+            let name = match solver.variant {
+                Some(variant) => format!("part{} ({})", solver.part, variant),
+                None => format!("part{}", solver.part),
+            };
+            println!("  {:<20} {}::{}", name, solver.module, solver.function);
+        }
+    }
+}
+
+fn new(args: &[String]) -> Result<bool> {
+    let day = match args {
+        [day] => parse_day(day)?,
+        _ => return Err(anyhow!("Usage: advent_of_code_2020 new <day>")),
+    };
+    for file in scaffold::create(env!("CARGO_MANIFEST_DIR"), day)? {
+        println!("Wrote {}", file.display());
+    }
+    Ok(true)
+}
+
+fn fetch(args: &[String]) -> Result<bool> {
+    let day = match args {
+        [day] => parse_day(day)?,
+        _ => return Err(anyhow!("Usage: advent_of_code_2020 fetch <day>")),
+    };
+    let config = fetch::Config::load()?;
+    let (path, downloaded) = fetch::fetch(&config, day)?;
+    if downloaded {
+        println!("Wrote {}", path.display());
+    } else {
+        println!("Already cached at {}", path.display());
+    }
+    Ok(true)
+}
+
+fn submit(args: &[String]) -> Result<bool> {
+    let (day, part) = match args {
+        [day, part, ..] => (parse_day(day)?, part.parse().context("Invalid part")?),
+        _ => return Err(anyhow!("Missing day or part")),
+    };
+    let options = parse_run_options(&[&args[..1], &args[2..]].concat())?;
+    if options.part.is_some()
+        || options.inputs.is_some()
+        || options.record
+        || options.explain
+        || options.dump_parsed
+        || options.parsed_input
+        || options.target.is_some()
+        || options.k.is_some()
+    {
+        return Err(anyhow!(
+            "submit only takes --variant, --input and --answers"
+        ));
+    }
+    // Without --variant, the part's first solver, as in cross-check.
+    let mut solvers: Vec<_> = registry::for_day(day)
+        .filter(|s| s.part == part)
+        .filter(|s| match &options.variant {
+            Some(variant) => s.is_variant(Some(variant)),
+            None => true,
+        })
+        .collect();
+    solvers.sort_by_key(|s| s.variant.is_some());
+    let solver = *solvers
+        .first()
+        .ok_or_else(|| anyhow!("No solver of day{} part{} matches", day, part))?;
+    let input = read_input(day, options.input.as_deref())?;
+    let answer = run_solver(solver, &input)
+        .answer
+        .map_err(|err| anyhow!(err))?;
+    let hash = answers::input_hash(&input);
+
+    let mut log = Submissions::load(submit::DEFAULT_PATH)?;
+    let config = fetch::Config::load()?;
+    let attempt = match submit::submit(&config, &mut log, day, part, &hash, &answer, submit::now())?
+    {
+        Submission::Refused(reason) => {
+            println!("Not submitting {}: {}", answer, reason);
+            return Ok(false);
+        }
+        Submission::Sent(attempt) => attempt,
+    };
+    log.save(submit::DEFAULT_PATH)?;
+    match attempt.wait {
+        Some(wait) => println!("{}: {} (wait {}s)", answer, attempt.outcome, wait),
+        None => println!("{}: {}", answer, attempt.outcome),
+    }
+    if attempt.outcome == Outcome::Correct {
+        let answers_path = options
+            .answers
+            .unwrap_or_else(|| answers::DEFAULT_PATH.to_owned());
+        let mut store = AnswerStore::load(&answers_path)?;
+        if store.get(day, part, &hash).is_none() {
+            store.record(day, part, &hash, &answer);
+            store.save(&answers_path)?;
+            println!("Recorded in {}", answers_path);
+        }
+    }
+    Ok(matches!(
+        attempt.outcome,
+        Outcome::Correct | Outcome::AlreadySolved
+    ))
+}
+
+fn passports(args: &[String]) -> Result<bool> {
+    let mut csv = false;
+    let mut schema = PassportSchema::strict();
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--format" => {
+                csv = match value()?.as_str() {
+                    "jsonl" => false,
+                    "csv" => true,
+                    other => {
+                        return Err(anyhow!("Unknown format {:?}, expected jsonl or csv", other))
+                    }
+                }
+            }
+            "--presence" => schema = PassportSchema::presence(),
+            "--input" | "-i" => path = Some(value()?.as_str()),
+            other => return Err(anyhow!("Unexpected argument {:?}", other)),
+        }
+    }
+    let input = input::normalize(&read_input(4, path)?);
+    let records = day4::export_records(&input, &schema);
+    let stdout = io::stdout();
+    if csv {
+        day4::write_csv(&records, stdout.lock())?;
+    } else {
+        day4::write_json_lines(&records, stdout.lock())?;
+    }
+    Ok(true)
+}
+
+// Terminal size for plot.
+const PLOT_COLUMNS: usize = 80;
+const PLOT_ROWS: usize = 24;
+
+fn plot(args: &[String]) -> Result<bool> {
+    let day = match args.first() {
+        Some(day) => parse_day(day)?,
+        None => return Err(anyhow!("Missing day")),
+    };
+    if day != 12 {
+        return Err(anyhow!("Day {} has nothing to plot", day));
+    }
+    let mut part = 1;
+    let mut svg = None;
+    let mut path = None;
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--part" | "-p" => part = value()?.parse().context("Invalid part")?,
+            "--svg" => svg = Some(value()?.as_str()),
+            "--input" | "-i" => path = Some(value()?.as_str()),
+            other => return Err(anyhow!("Unexpected argument {:?}", other)),
+        }
+    }
+    let actions = day12::parse_actions(&input::normalize(&read_input(day, path)?))?;
+    match part {
+        1 => draw_route(Navigator::new(Direct), &actions, svg),
+        2 => draw_route(Navigator::new(Waypoint), &actions, svg),
+        _ => Err(anyhow!("Day 12 has no part {}", part)),
+    }
+}
+
+fn draw_route<M: MovementModel>(
+    mut navigator: Navigator<M>,
+    actions: &[Action],
+    svg: Option<&str>,
+) -> Result<bool> {
+    navigator.process_all(actions);
+    println!("{}", navigator.to_ascii(PLOT_COLUMNS, PLOT_ROWS));
+    if let Some(svg) = svg {
+        navigator
+            .write_svg(svg)
+            .with_context(|| format!("Could not write {}", svg))?;
+        println!("Wrote {}", svg);
+    }
+    Ok(true)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]),
+        Some("new") => new(&args[1..]),
+        Some("fetch") => fetch(&args[1..]),
+        Some("submit") => submit(&args[1..]),
+        Some("passports") => passports(&args[1..]),
+        Some("plot") => plot(&args[1..]),
+        Some("list") => {
+            list();
+            Ok(true)
+        }
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        _ => Err(anyhow!(USAGE)),
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("{:#}", err);
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::answers::{input_hash, AnswerStore, Verdict};
+use crate::baseline::format_duration;
+use crate::crosscheck::run_solver;
+use crate::examples::{self, Example};
+use crate::registry::RegisteredSolver;
+use anyhow::Result;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+// Named inputs use the examples layout, <dir>/dayNN/<name>.txt, so that a
+// team can drop everyone's input for a day into one directory. Answers listed
+// in <dir>/dayNN/answers.toml are checked before the answer store's.
+pub fn load_inputs<P: AsRef<Path>>(dir: P, day: u32) -> Result<Vec<Example>> {
+    examples::load(dir, day)
+}
+
+#[derive(Debug)]
+pub struct Cell {
+    pub answer: Result<String, String>,
+    pub time: Duration,
+    pub verdict: Option<Verdict>,
+}
+
+#[derive(Debug)]
+pub struct Row {
+    pub solver: &'static RegisteredSolver,
+    pub cells: Vec<Cell>,
+}
+
+// Every solver run on every named input of a day, one column per input.
+#[derive(Debug)]
+pub struct Matrix {
+    pub day: u32,
+    pub inputs: Vec<String>,
+    pub hashes: Vec<String>,
+    pub rows: Vec<Row>,
+}
+
+impl Matrix {
+    // The solvers of a part should come first without a variant, the first one
+    // of each part is the reference for the others.
+    pub fn run(
+        day: u32,
+        solvers: &[&'static RegisteredSolver],
+        inputs: &[Example],
+        store: &AnswerStore,
+    ) -> Self {
+        let hashes: Vec<String> = inputs
+            .iter()
+            .map(|example| input_hash(&example.input))
+            .collect();
+        let rows = solvers
+            .iter()
+            .map(|&solver| Row {
+                solver,
+                cells: inputs
+                    .iter()
+                    .zip(hashes.iter())
+                    .map(|(example, hash)| {
+                        let outcome = run_solver(solver, &example.input);
+                        Cell {
+                            verdict: outcome.answer.as_ref().ok().map(|answer| {
+                                match example.expected.get(&solver.part) {
+                                    Some(expected) if expected == answer => Verdict::Correct,
+                                    Some(expected) => Verdict::Wrong {
+                                        expected: expected.clone(),
+                                    },
+                                    None => store.check(day, solver.part, hash, answer),
+                                }
+                            }),
+                            answer: outcome.answer,
+                            time: outcome.generate_time + outcome.solve_time,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        Matrix {
+            day,
+            inputs: inputs.iter().map(|example| example.name.clone()).collect(),
+            hashes,
+            rows,
+        }
+    }
+
+    fn reference(&self, part: u32) -> &Row {
+        self.rows
+            .iter()
+            .find(|row| row.solver.part == part)
+            .unwrap()
+    }
+
+    fn cell_ok(&self, row: &Row, column: usize) -> bool {
+        let cell = &row.cells[column];
+        cell.answer.is_ok()
+            && !matches!(cell.verdict, Some(Verdict::Wrong { .. }))
+            && cell.answer == self.reference(row.solver.part).cells[column].answer
+    }
+
+    // Which solvers failed, contradict the store or disagree with their
+    // part's reference, on which input.
+    pub fn failures(&self) -> Vec<String> {
+        let mut failures = Vec::new();
+        for row in self.rows.iter() {
+            for (column, name) in self.inputs.iter().enumerate() {
+                if !self.cell_ok(row, column) {
+                    failures.push(format!("{} on {}", row.solver, name));
+                }
+            }
+        }
+        failures
+    }
+
+    // Stores the reference answers the store does not know yet, where every
+    // solver of the part agrees. Returns how many were added.
+    pub fn record(&self, store: &mut AnswerStore) -> usize {
+        let mut recorded = 0;
+        for row in self.rows.iter() {
+            if !std::ptr::eq(row, self.reference(row.solver.part)) {
+                continue;
+            }
+            for (column, hash) in self.hashes.iter().enumerate() {
+                let agrees = self
+                    .rows
+                    .iter()
+                    .filter(|other| other.solver.part == row.solver.part)
+                    .all(|other| self.cell_ok(other, column));
+                if let (true, Some(Verdict::Unknown), Ok(answer)) = (
+                    agrees,
+                    &row.cells[column].verdict,
+                    &row.cells[column].answer,
+                ) {
+                    store.record(self.day, row.solver.part, hash, answer);
+                    recorded += 1;
+                }
+            }
+        }
+        recorded
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| match row.solver.variant {
+                Some(variant) => format!("part{} ({})", row.solver.part, variant),
+                None => format!("part{}", row.solver.part),
+            })
+            .collect();
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.cells
+                    .iter()
+                    .map(|cell| match (&cell.answer, &cell.verdict) {
+                        (Ok(answer), Some(verdict)) => format!(
+                            "{} {} {}",
+                            answer,
+                            verdict.mark(),
+                            format_duration(cell.time.as_nanos() as f64)
+                        ),
+                        (Ok(answer), None) => answer.clone(),
+                        (Err(err), _) => format!("error: {}", err),
+                    })
+                    .collect()
+            })
+            .collect();
+        let label_width = labels
+            .iter()
+            .map(|label| label.len())
+            .max()
+            .unwrap_or(0)
+            .max("day25".len());
+        let widths: Vec<usize> = self
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(column, name)| {
+                cells
+                    .iter()
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max(name.len())
+            })
+            .collect();
+
+        let header = format!("day{}", self.day);
+        let lines = std::iter::once((&header, &self.inputs)).chain(labels.iter().zip(cells.iter()));
+        for (label, columns) in lines {
+            let mut line = format!("{:<width$}", label, width = label_width);
+            for (column, width) in columns.iter().zip(widths.iter()) {
+                line += &format!("  {:<width$}", column, width = width);
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[test]
+    fn matrix() {
+        let mut solvers: Vec<_> = registry::for_day(3).collect();
+        solvers.sort_by_key(|s| (s.part, s.variant.is_some()));
+        let example = |name: &str, input: &str| Example {
+            day: 3,
+            name: name.to_owned(),
+            input: input.to_owned(),
+            expected: BTreeMap::new(),
+            ignored: BTreeSet::new(),
+        };
+        let mut inputs = vec![
+            example("sample", "..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#\n"),
+            example("tiny", "..\n.#\n"),
+        ];
+        let mut store = AnswerStore::default();
+        store.record(3, 1, &input_hash(&inputs[1].input), "2");
+
+        let matrix = Matrix::run(3, &solvers, &inputs, &store);
+        assert_eq!(matrix.rows.len(), 3);
+        assert_eq!(matrix.rows[0].cells[0].answer, Ok("7".to_owned()));
+        assert_eq!(matrix.failures(), vec!["day3 part1 on tiny"]);
+        let table = matrix.to_string();
+        assert!(table.starts_with("day3"));
+        assert!(table.contains("part2 (Iterator)"));
+        assert!(table.contains("336 ?"));
+
+        assert_eq!(matrix.record(&mut store), 3);
+        let rerun = Matrix::run(3, &solvers, &inputs, &store);
+        assert_eq!(rerun.rows[2].cells[0].verdict, Some(Verdict::Correct));
+
+        // The input's own answers.toml wins over the store.
+        inputs[0].expected.insert(1, "8".to_owned());
+        inputs[1].expected.insert(1, "1".to_owned());
+        let checked = Matrix::run(3, &solvers, &inputs, &store);
+        assert_eq!(
+            checked.rows[0].cells[0].verdict,
+            Some(Verdict::Wrong {
+                expected: "8".to_owned()
+            })
+        );
+        assert_eq!(checked.rows[0].cells[1].verdict, Some(Verdict::Correct));
+        assert_eq!(checked.failures(), vec!["day3 part1 on sample"]);
+    }
+}
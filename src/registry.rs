@@ -0,0 +1,117 @@
+use crate::fetch;
+use crate::input;
+use aoc_runner::{ArcStr, Runner};
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+pub type Factory = fn(ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+// One `#[aoc]` solver. The factory runs the day's generator and returns a
+// runner for the solver.
+#[derive(Clone, Copy)]
+pub struct RegisteredSolver {
+    pub day: u32,
+    pub part: u32,
+    pub variant: Option<&'static str>,
+    pub module: &'static str,
+    pub function: &'static str,
+    // The day's `#[aoc_generator]`, if it has one.
+    pub generator: Option<&'static str>,
+    factory: Factory,
+}
+
+impl RegisteredSolver {
+    pub fn generate(&self, input: &str) -> Result<Box<dyn Runner>, Box<dyn Error>> {
+        (self.factory)(ArcStr::from(input::normalize(input).as_str()))
+    }
+
+    pub fn is_variant(&self, variant: Option<&str>) -> bool {
+        match (self.variant, variant) {
+            (None, None) => true,
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RegisteredSolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day{} part{}", self.day, self.part)?;
+        if let Some(variant) = self.variant {
+            write!(f, " ({})", variant)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RegisteredSolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisteredSolver")
+            .field("day", &self.day)
+            .field("part", &self.part)
+            .field("variant", &self.variant)
+            .field("module", &self.module)
+            .field("function", &self.function)
+            .field("generator", &self.generator)
+            .finish()
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/registry.rs"));
+
+pub fn solvers() -> &'static [RegisteredSolver] {
+    SOLVERS
+}
+
+pub fn days() -> Vec<u32> {
+    let mut days: Vec<u32> = SOLVERS.iter().map(|s| s.day).collect();
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+pub fn for_day(day: u32) -> impl Iterator<Item = &'static RegisteredSolver> {
+    SOLVERS.iter().filter(move |s| s.day == day)
+}
+
+pub fn find(day: u32, part: u32, variant: Option<&str>) -> Option<&'static RegisteredSolver> {
+    for_day(day).find(|s| s.part == part && s.is_variant(variant))
+}
+
+// Where aoc-runner expects the puzzle inputs, falling back to the input
+// `fetch` cached for the configured user.
+pub fn input_path(day: u32) -> PathBuf {
+    let path = PathBuf::from(format!("input/2020/day{}.txt", day));
+    if path.exists() {
+        return path;
+    }
+    fetch::Config::load()
+        .ok()
+        .and_then(|config| config.cache_path(day))
+        .filter(|cached| cached.is_file())
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered() {
+        assert_eq!(days(), (1..=20).collect::<Vec<u32>>());
+        let iterator = find(3, 2, Some("iterator")).unwrap();
+        assert_eq!(iterator.to_string(), "day3 part2 (Iterator)");
+        assert_eq!(iterator.function, "part2_iterator");
+        assert_eq!(iterator.generator, Some("input_generator"));
+        assert_eq!(find(1, 1, Some("Chars")).unwrap().generator, None);
+        assert_eq!(for_day(11).count(), 5);
+        assert!(find(3, 3, None).is_none());
+
+        let runner = find(1, 1, Some("Chars"))
+            .unwrap()
+            .generate("1721\n979\n366\n299\n675\n1456\n")
+            .unwrap();
+        assert_eq!(runner.try_run().unwrap().to_string(), "514579");
+    }
+}
@@ -0,0 +1,179 @@
+use crate::examples;
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn module_source(day: u32) -> String {
+    format!(
+        r#"use anyhow::Result;
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+pub fn parse_input(input: &str) -> Result<Vec<String>> {{
+    Ok(input.lines().map(|s| s.trim().to_owned()).collect())
+}}
+
+#[aoc_generator(day{day})]
+pub fn input_generator(input: &str) -> Vec<String> {{
+    parse_input(input).expect("Could not parse the input")
+}}
+
+#[aoc(day{day}, part1)]
+pub fn part1(input: &[String]) -> usize {{
+    input.len()
+}}
+
+#[aoc(day{day}, part2)]
+pub fn part2(input: &[String]) -> usize {{
+    input.len()
+}}
+
+solution!(Day{day}, {day}, Vec<String>, parse_input, part1, part2);
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../examples/day{day:02}/sample.txt");
+
+    #[test]
+    fn sample1() {{
+        assert_eq!(part1(&input_generator(SAMPLE)), 0);
+    }}
+
+    #[test]
+    fn sample2() {{
+        assert_eq!(part2(&input_generator(SAMPLE)), 0);
+    }}
+}}
+"#,
+        day = day
+    )
+}
+
+fn day_module(line: &str) -> Option<u32> {
+    line.trim()
+        .strip_prefix("pub mod day")?
+        .strip_suffix(';')?
+        .parse()
+        .ok()
+}
+
+// Adds `pub mod dayN;` to lib.rs, keeping the day modules in order.
+pub fn register(lib: &str, day: u32) -> Result<String> {
+    let lines: Vec<&str> = lib.lines().collect();
+    if lines.iter().any(|line| day_module(line) == Some(day)) {
+        return Err(anyhow!("day{} is already registered", day));
+    }
+    let at = match lines
+        .iter()
+        .rposition(|line| matches!(day_module(line), Some(d) if d < day))
+    {
+        Some(previous) => previous + 1,
+        None => lines
+            .iter()
+            .position(|line| day_module(line).is_some())
+            .ok_or_else(|| anyhow!("No day modules in lib.rs"))?,
+    };
+    let module = format!("pub mod day{};", day);
+    let mut result: Vec<&str> = lines[..at].to_vec();
+    result.push(&module);
+    result.extend_from_slice(&lines[at..]);
+    Ok(result.join("\n") + "\n")
+}
+
+// Writes src/dayN.rs, registers it in src/lib.rs and creates an empty example
+// with its answers manifest, returning the files it touched. A module that
+// already exists but is not in lib.rs is only registered.
+pub fn create<P: AsRef<Path>>(root: P, day: u32) -> Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&day) {
+        return Err(anyhow!("There is no day {}", day));
+    }
+    let root = root.as_ref();
+    let lib_path = root.join("src/lib.rs");
+    let lib = fs::read_to_string(&lib_path)
+        .with_context(|| format!("Could not read {}", lib_path.display()))?;
+    let lib = register(&lib, day)?;
+
+    let mut files = Vec::new();
+    let module = root.join(format!("src/day{}.rs", day));
+    if !module.exists() {
+        fs::write(&module, module_source(day))?;
+        files.push(module);
+    }
+    let example_dir = examples::day_dir(root.join(examples::DIR), day);
+    fs::create_dir_all(&example_dir)?;
+    let example = example_dir.join("sample.txt");
+    if !example.exists() {
+        fs::write(&example, "")?;
+        files.push(example);
+    }
+    let manifest = example_dir.join(examples::MANIFEST);
+    if !manifest.exists() {
+        fs::write(&manifest, "[sample]\n# part1 = 0\n# part2 = 0\n")?;
+        files.push(manifest);
+    }
+    fs::write(&lib_path, lib)?;
+    files.push(lib_path);
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn register_in_order() {
+        let lib = "use aoc_runner_derive::aoc_lib;\n\npub mod day1;\npub mod day2;\npub mod day10;\n\npub mod registry;\n";
+        assert_eq!(
+            register(lib, 3).unwrap(),
+            "use aoc_runner_derive::aoc_lib;\n\npub mod day1;\npub mod day2;\npub mod day3;\npub mod day10;\n\npub mod registry;\n"
+        );
+        assert!(register(lib, 11)
+            .unwrap()
+            .contains("pub mod day10;\npub mod day11;\n\n"));
+        assert!(register("pub mod day2;\n", 1)
+            .unwrap()
+            .starts_with("pub mod day1;\npub mod day2;"));
+        assert!(register(lib, 2).is_err());
+    }
+
+    #[test]
+    fn create_day() {
+        let root = env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod day1;\n").unwrap();
+
+        let files = create(&root, 21).unwrap();
+        assert_eq!(files.len(), 4);
+        let module = fs::read_to_string(root.join("src/day21.rs")).unwrap();
+        assert!(module.contains("#[aoc(day21, part2)]"));
+        assert!(module.contains("solution!(Day21, 21,"));
+        assert!(module.contains(r#"include_str!("../examples/day21/sample.txt")"#));
+        assert!(module_source(3).contains("../examples/day03/sample.txt"));
+        assert_eq!(
+            fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+            "pub mod day1;\npub mod day21;\n"
+        );
+        assert!(root.join("examples/day21/sample.txt").is_file());
+        assert!(examples::load(root.join("examples"), 21).unwrap()[0]
+            .expected
+            .is_empty());
+
+        assert!(create(&root, 21).is_err());
+        assert!(create(&root, 26).is_err());
+
+        fs::write(root.join("src/day22.rs"), "// solved\n").unwrap();
+        let files = create(&root, 22).unwrap();
+        assert!(!files.contains(&root.join("src/day22.rs")));
+        assert_eq!(
+            fs::read_to_string(root.join("src/day22.rs")).unwrap(),
+            "// solved\n"
+        );
+        assert!(fs::read_to_string(root.join("src/lib.rs"))
+            .unwrap()
+            .ends_with("pub mod day21;\npub mod day22;\n"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
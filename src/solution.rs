@@ -0,0 +1,369 @@
+use crate::crosscheck::describe_panic;
+use crate::input;
+use anyhow::{anyhow, Context, Result};
+use num_bigint::{BigInt, BigUint};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    BigInt(BigInt),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::BigInt(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::Int(n as i128)
+            }
+        })*
+    };
+}
+
+answer_from_int!(i32, u32, i64, u64, usize);
+
+impl From<u128> for Answer {
+    fn from(n: u128) -> Self {
+        i128::try_from(n)
+            .map(Answer::Int)
+            .unwrap_or_else(|_| Answer::BigInt(n.into()))
+    }
+}
+
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Self {
+        Answer::BigInt(n)
+    }
+}
+
+impl From<BigUint> for Answer {
+    fn from(n: BigUint) -> Self {
+        Answer::BigInt(n.into())
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+// How a day arrived at its answer, for the days that can tell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Explanation {
+    // The input entries the answer combines.
+    Entries(Vec<i64>),
+    // A contiguous run of the input, `start..end` by index.
+    Range {
+        start: usize,
+        end: usize,
+        values: Vec<u64>,
+    },
+    Seat {
+        row: u32,
+        column: u32,
+        id: usize,
+    },
+    // Field name and the ticket column it maps to, by column.
+    Fields(Vec<(String, usize)>),
+    Grid(Vec<String>),
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Explanation::Entries(entries) => {
+                write!(f, "entries:")?;
+                for entry in entries {
+                    write!(f, " {}", entry)?;
+                }
+                Ok(())
+            }
+            Explanation::Range { start, end, values } => {
+                write!(f, "range {}..{}:", start, end)?;
+                for value in values {
+                    write!(f, " {}", value)?;
+                }
+                Ok(())
+            }
+            Explanation::Seat { row, column, id } => {
+                write!(f, "row {}, column {}, seat id {}", row, column, id)
+            }
+            Explanation::Fields(fields) => {
+                for (i, (name, column)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "column {:>2}: {}", column, name)?;
+                }
+                Ok(())
+            }
+            Explanation::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+pub struct Variant<I> {
+    pub part: u32,
+    pub name: &'static str,
+    pub solve: fn(&I) -> Answer,
+}
+
+// A day's parser and solvers behind one type. Parsing reports invalid input
+// as an error; the solvers are the `#[aoc]` functions, which may still panic,
+// so `solve` catches that. The parsed input serializes so that it can be
+// dumped and read back as JSON.
+pub trait Solution {
+    const DAY: u32;
+    type Input: Serialize + DeserializeOwned;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+    // The named `#[aoc]` solvers, by part.
+    fn variants() -> Vec<Variant<Self::Input>>;
+    fn explain(part: u32, input: &Self::Input) -> Option<Explanation>;
+}
+
+// Implements `Solution` for a day module:
+// solution!(Day3, 3, Grid<bool>, parse_map, part1, part2, 2 "Iterator" => part2_iterator);
+// The parser returns `anyhow::Result<Input>`. An `explain = function` argument, taking the part and the parsed input,
+// can follow the variants.
+macro_rules! solution {
+    (@explain $part:ident, $input:ident) => {{
+        let _ = ($part, $input);
+        None
+    }};
+    (@explain $part:ident, $input:ident, $explain:path) => {
+        $explain($part, $input)
+    };
+    ($name:ident, $day:expr, $input:ty, $parse:expr, $part1:path, $part2:path
+     $(, $part:literal $variant:literal => $solver:path)*
+     $(, explain = $explain:path)? $(,)?) => {
+        pub struct $name;
+
+        impl $crate::solution::Solution for $name {
+            const DAY: u32 = $day;
+            type Input = $input;
+
+            fn parse(input: &str) -> anyhow::Result<Self::Input> {
+                ($parse)(input)
+            }
+
+            fn part1(input: &Self::Input) -> $crate::solution::Answer {
+                $part1(input).into()
+            }
+
+            fn part2(input: &Self::Input) -> $crate::solution::Answer {
+                $part2(input).into()
+            }
+
+            fn variants() -> Vec<$crate::solution::Variant<Self::Input>> {
+                vec![$($crate::solution::Variant {
+                    part: $part,
+                    name: $variant,
+                    solve: |input| $solver(input).into(),
+                }),*]
+            }
+
+            fn explain(part: u32, input: &Self::Input) -> Option<$crate::solution::Explanation> {
+                solution!(@explain part, input $(, $explain)?)
+            }
+        }
+    };
+}
+
+fn catch<T>(f: impl FnOnce() -> T) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| anyhow!(describe_panic(payload)))
+}
+
+fn solver<S: Solution>(part: u32, variant: Option<&str>) -> Result<fn(&S::Input) -> Answer> {
+    Ok(match (part, variant) {
+        (1, None) => S::part1,
+        (2, None) => S::part2,
+        (_, None) => return Err(anyhow!("day{} has no part{}", S::DAY, part)),
+        (_, Some(name)) => {
+            S::variants()
+                .into_iter()
+                .find(|v| v.part == part && v.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| anyhow!("day{} part{} has no variant {:?}", S::DAY, part, name))?
+                .solve
+        }
+    })
+}
+
+fn parse<S: Solution>(input: &str) -> Result<S::Input> {
+    S::parse(&input::normalize(input)).context("Could not parse the input")
+}
+
+fn dispatch<S: Solution>(part: u32, variant: Option<&str>, input: &str) -> Result<Answer> {
+    let solve = solver::<S>(part, variant)?;
+    let input = parse::<S>(input)?;
+    catch(|| solve(&input))
+}
+
+// Like `dispatch`, for an input already parsed and dumped as JSON.
+fn dispatch_parsed<S: Solution>(part: u32, variant: Option<&str>, json: &str) -> Result<Answer> {
+    let solve = solver::<S>(part, variant)?;
+    let input: S::Input = serde_json::from_str(json).context("Invalid parsed input")?;
+    catch(|| solve(&input))
+}
+
+fn dump<S: Solution>(input: &str) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&parse::<S>(input)?)?)
+}
+
+fn explain_part<S: Solution>(part: u32, input: &str) -> Result<Option<Explanation>> {
+    let input = parse::<S>(input)?;
+    catch(|| S::explain(part, &input))
+}
+
+fn variant_names<S: Solution>() -> Vec<(u32, &'static str)> {
+    S::variants().iter().map(|v| (v.part, v.name)).collect()
+}
+
+struct Entry {
+    day: u32,
+    solve: fn(u32, Option<&str>, &str) -> Result<Answer>,
+    solve_parsed: fn(u32, Option<&str>, &str) -> Result<Answer>,
+    dump: fn(&str) -> Result<String>,
+    variants: fn() -> Vec<(u32, &'static str)>,
+    explain: fn(u32, &str) -> Result<Option<Explanation>>,
+}
+
+macro_rules! entries {
+    ($($module:ident::$name:ident),* $(,)?) => {
+        &[$(Entry {
+            day: <crate::$module::$name as Solution>::DAY,
+            solve: dispatch::<crate::$module::$name>,
+            solve_parsed: dispatch_parsed::<crate::$module::$name>,
+            dump: dump::<crate::$module::$name>,
+            variants: variant_names::<crate::$module::$name>,
+            explain: explain_part::<crate::$module::$name>,
+        }),*]
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/solutions.rs"));
+
+fn entry(day: u32) -> Result<&'static Entry> {
+    SOLUTIONS
+        .iter()
+        .find(|e| e.day == day)
+        .ok_or_else(|| anyhow!("No solution for day{}", day))
+}
+
+pub fn days() -> Vec<u32> {
+    SOLUTIONS.iter().map(|e| e.day).collect()
+}
+
+pub fn variants(day: u32) -> Result<Vec<(u32, &'static str)>> {
+    Ok((entry(day)?.variants)())
+}
+
+pub fn solve(day: u32, part: u32, input: &str) -> Result<Answer> {
+    (entry(day)?.solve)(part, None, input)
+}
+
+pub fn solve_variant(day: u32, part: u32, variant: &str, input: &str) -> Result<Answer> {
+    (entry(day)?.solve)(part, Some(variant), input)
+}
+
+pub fn explain(day: u32, part: u32, input: &str) -> Result<Option<Explanation>> {
+    (entry(day)?.explain)(part, input)
+}
+
+// The generator's output as pretty-printed JSON.
+pub fn dump_parsed(day: u32, input: &str) -> Result<String> {
+    (entry(day)?.dump)(input)
+}
+
+// Solves a part from the JSON `dump_parsed` writes, possibly edited by hand.
+pub fn solve_parsed(day: u32, part: u32, variant: Option<&str>, json: &str) -> Result<Answer> {
+    (entry(day)?.solve_parsed)(part, variant, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn dispatch() {
+        let sample = "1721\n979\n366\n299\n675\n1456\n";
+        assert_eq!(solve(1, 1, sample).unwrap(), Answer::Int(514579));
+        assert_eq!(
+            solve_variant(1, 2, "chars", sample).unwrap().to_string(),
+            "241861950"
+        );
+        assert!(solve(1, 3, sample).is_err());
+        assert!(solve(25, 1, sample).is_err());
+        assert!(solve_variant(1, 1, "Bits", sample).is_err());
+        assert!(solve(1, 1, "1\n2\n").is_err());
+        assert!(solve(5, 1, "FBFBBFFRLX").is_err());
+        assert_eq!(
+            explain(1, 2, sample).unwrap(),
+            Some(Explanation::Entries(vec![979, 366, 675]))
+        );
+        assert_eq!(explain(2, 1, "1-3 a: abcde\n").unwrap(), None);
+        assert_eq!(
+            Answer::from(u128::MAX).to_string(),
+            "340282366920938463463374607431768211455"
+        );
+    }
+
+    // Every example solves part 1 the same after a round trip through JSON.
+    // Part 2 of some days takes long enough that the example tests suffice.
+    #[test]
+    fn parsed_round_trip() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(crate::examples::DIR);
+        for day in days() {
+            for example in crate::examples::load(&dir, day).unwrap() {
+                let json = dump_parsed(day, &example.input).unwrap();
+                if example.expected.contains_key(&1) {
+                    assert_eq!(
+                        solve_parsed(day, 1, None, &json).unwrap(),
+                        solve(day, 1, &example.input).unwrap(),
+                        "day{} on {}",
+                        day,
+                        example.name
+                    );
+                }
+            }
+        }
+        assert!(solve_parsed(1, 1, None, "[1721, 979]").is_err());
+        assert!(dump_parsed(13, "").is_err());
+    }
+
+    // Every `#[aoc]` solver is reachable through the dispatch table.
+    #[test]
+    fn matches_registry() {
+        assert_eq!(days(), registry::days());
+        for solver in registry::solvers() {
+            if let Some(variant) = solver.variant {
+                assert!(
+                    variants(solver.day)
+                        .unwrap()
+                        .contains(&(solver.part, variant)),
+                    "{} is missing",
+                    solver
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use crate::fetch::{self, Config};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Attempts depend on the account, so they live next to the cached inputs
+// rather than in the committed answers.toml.
+pub const DEFAULT_PATH: &str = "input/submissions.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    // Rate limited, the answer was not checked.
+    Wait,
+    AlreadySolved,
+    Unrecognised,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Outcome::Correct => "correct",
+            Outcome::TooHigh => "too high",
+            Outcome::TooLow => "too low",
+            Outcome::Wrong => "wrong",
+            Outcome::Wait => "rate limited",
+            Outcome::AlreadySolved => "already solved",
+            Outcome::Unrecognised => "unrecognised response",
+        };
+        f.write_str(text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attempt {
+    pub day: u32,
+    pub part: u32,
+    // The account, as Config::user_key names it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub input: String,
+    pub answer: String,
+    pub outcome: Outcome,
+    // Seconds since the epoch.
+    pub time: u64,
+    // Seconds the server asked us to wait before the next attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Submissions {
+    #[serde(default, rename = "attempt")]
+    pub attempts: Vec<Attempt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Submission {
+    Refused(String),
+    Sent(Attempt),
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// "You have 1m 23s left to wait" or "Please wait one minute before trying
+// again", in seconds.
+fn parse_wait(text: &str) -> Option<u64> {
+    if let Some(end) = text.find(" left to wait") {
+        let start = text[..end].rfind("You have ")? + "You have ".len();
+        return text[start..end]
+            .split_whitespace()
+            .map(|amount| {
+                let (number, unit) = amount.split_at(amount.char_indices().last()?.0);
+                let number: u64 = number.parse().ok()?;
+                match unit {
+                    "h" => Some(number * 3600),
+                    "m" => Some(number * 60),
+                    "s" => Some(number),
+                    _ => None,
+                }
+            })
+            .sum();
+    }
+    let start = text.find("wait ")? + "wait ".len();
+    let mut words = text[start..].split_whitespace();
+    let number = match words.next()? {
+        "one" | "a" => 1,
+        number => number.parse().ok()?,
+    };
+    match words.next()?.trim_end_matches(|c: char| !c.is_alphabetic()) {
+        "minute" | "minutes" => Some(number * 60),
+        "second" | "seconds" => Some(number),
+        _ => None,
+    }
+}
+
+// Reads the outcome from the page the server answers a submission with.
+pub fn parse_response(html: &str) -> (Outcome, Option<u64>) {
+    let article = match (html.find("<article"), html.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+    let text = strip_tags(article);
+    let outcome = if text.contains("That's the right answer") {
+        Outcome::Correct
+    } else if text.contains("answer too recently") {
+        Outcome::Wait
+    } else if text.contains("your answer is too high") {
+        Outcome::TooHigh
+    } else if text.contains("your answer is too low") {
+        Outcome::TooLow
+    } else if text.contains("That's not the right answer") {
+        Outcome::Wrong
+    } else if text.contains("Did you already complete it?") {
+        Outcome::AlreadySolved
+    } else {
+        Outcome::Unrecognised
+    };
+    let wait = match outcome {
+        Outcome::Correct | Outcome::AlreadySolved => None,
+        _ => parse_wait(&text),
+    };
+    (outcome, wait)
+}
+
+impl Submissions {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Submissions::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Invalid submission log {}", path.display()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn for_part<'a>(
+        &'a self,
+        user: Option<&'a str>,
+        day: u32,
+        part: u32,
+        input: &'a str,
+    ) -> impl Iterator<Item = &'a Attempt> {
+        self.attempts.iter().filter(move |a| {
+            a.user.as_deref() == user && a.day == day && a.part == part && a.input == input
+        })
+    }
+
+    // Why this answer cannot be right or cannot be sent yet, judging by the
+    // earlier attempts of the same user on the same input. Numeric answers are
+    // also held against every "too high" and "too low".
+    pub fn refusal(
+        &self,
+        user: Option<&str>,
+        day: u32,
+        part: u32,
+        input: &str,
+        answer: &str,
+        now: u64,
+    ) -> Option<String> {
+        if let Some(wait) = self
+            .attempts
+            .iter()
+            .filter(|a| a.user.as_deref() == user && a.day == day && a.input == input)
+            .filter_map(|a| Some(a.time + a.wait?))
+            .max()
+            .filter(|until| *until > now)
+        {
+            return Some(format!("Rate limited, wait another {}s", wait - now));
+        }
+        let value = answer.parse::<i128>().ok();
+        for attempt in self.for_part(user, day, part, input) {
+            let bound = attempt.answer.parse::<i128>().ok();
+            let reason = match (attempt.outcome, value, bound) {
+                (Outcome::Correct, _, _) => Some(format!("Already solved with {}", attempt.answer)),
+                (Outcome::TooHigh, _, _) | (Outcome::TooLow, _, _) | (Outcome::Wrong, _, _)
+                    if attempt.answer == answer =>
+                {
+                    Some(format!("{} was already {}", answer, attempt.outcome))
+                }
+                (Outcome::TooHigh, Some(value), Some(bound)) if value >= bound => {
+                    Some(format!("{} is too high, {} already was", answer, bound))
+                }
+                (Outcome::TooLow, Some(value), Some(bound)) if value <= bound => {
+                    Some(format!("{} is too low, {} already was", answer, bound))
+                }
+                _ => None,
+            };
+            if reason.is_some() {
+                return reason;
+            }
+        }
+        None
+    }
+}
+
+pub fn answer_url(config: &Config, day: u32) -> String {
+    format!("{}/{}/day/{}/answer", config.base_url(), fetch::YEAR, day)
+}
+
+pub fn post(config: &Config, day: u32, part: u32, answer: &str) -> Result<String> {
+    let url = answer_url(config, day);
+    let response = ureq::post(&url)
+        .set("Cookie", &format!("session={}", config.session()?))
+        .set("User-Agent", fetch::USER_AGENT)
+        .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+    match response {
+        Ok(response) => Ok(response.into_string()?),
+        Err(ureq::Error::Status(code, _)) => Err(anyhow!("{} answered {}", url, code)),
+        Err(err) => Err(err).context("Could not submit the answer"),
+    }
+}
+
+// Sends the answer unless earlier attempts rule it out, and logs the attempt.
+pub fn submit(
+    config: &Config,
+    log: &mut Submissions,
+    day: u32,
+    part: u32,
+    input: &str,
+    answer: &str,
+    now: u64,
+) -> Result<Submission> {
+    let user = config.user_key();
+    if let Some(reason) = log.refusal(user.as_deref(), day, part, input, answer, now) {
+        return Ok(Submission::Refused(reason));
+    }
+    let (outcome, wait) = parse_response(&post(config, day, part, answer)?);
+    let attempt = Attempt {
+        day,
+        part,
+        user,
+        input: input.to_owned(),
+        answer: answer.to_owned(),
+        outcome,
+        time: now,
+        wait,
+    };
+    log.attempts.push(attempt.clone());
+    Ok(Submission::Sent(attempt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    fn page(message: &str) -> String {
+        format!(
+            "<html><body><main><article><p>{}</p></article></main></body></html>",
+            message
+        )
+    }
+
+    #[test]
+    fn responses() {
+        assert_eq!(
+            parse_response(&page(
+                "That's the right answer! You are <em>one gold star</em> closer."
+            )),
+            (Outcome::Correct, None)
+        );
+        assert_eq!(
+            parse_response(&page("That's not the right answer; your answer is too high. Please wait one minute before trying again.")),
+            (Outcome::TooHigh, Some(60))
+        );
+        assert_eq!(
+            parse_response(&page("That's not the right answer. <a href=\"/2020/day/1\">[Return]</a> Please wait 5 minutes before trying again.")),
+            (Outcome::Wrong, Some(300))
+        );
+        assert_eq!(
+            parse_response(&page(
+                "You gave an answer too recently. You have 1m 23s left to wait."
+            )),
+            (Outcome::Wait, Some(83))
+        );
+        assert_eq!(
+            parse_response(&page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            )),
+            (Outcome::AlreadySolved, None)
+        );
+        assert_eq!(
+            parse_response("<html></html>"),
+            (Outcome::Unrecognised, None)
+        );
+        assert_eq!(parse_wait("You have 1m 2é left to wait"), None);
+    }
+
+    #[test]
+    fn submit_and_refuse() {
+        let server = MockServer::start(|request| {
+            let answer = request.body.rsplit("answer=").next().unwrap_or("");
+            let message = match answer {
+                "600000" => "That's not the right answer; your answer is too high.",
+                "400000" => "That's not the right answer; your answer is too low. Please wait one minute before trying again.",
+                "514579" => "That's the right answer!",
+                _ => "That's not the right answer.",
+            };
+            (200, page(message))
+        });
+        let config = Config {
+            session: Some("abc".to_owned()),
+            base_url: Some(server.url.clone()),
+            ..Config::default()
+        };
+        let mut log = Submissions::default();
+        let sent = |submission| match submission {
+            Submission::Sent(attempt) => attempt.outcome,
+            Submission::Refused(reason) => panic!("refused: {}", reason),
+        };
+
+        assert_eq!(
+            sent(submit(&config, &mut log, 1, 1, "hash", "600000", 1000).unwrap()),
+            Outcome::TooHigh
+        );
+        assert_eq!(
+            sent(submit(&config, &mut log, 1, 1, "hash", "400000", 1000).unwrap()),
+            Outcome::TooLow
+        );
+        assert_eq!(
+            submit(&config, &mut log, 1, 1, "hash", "500000", 1030).unwrap(),
+            Submission::Refused("Rate limited, wait another 30s".to_owned())
+        );
+        let someone = Config {
+            user: Some("someone".to_owned()),
+            ..config.clone()
+        };
+        assert_eq!(
+            sent(submit(&someone, &mut log, 1, 1, "hash", "500000", 1030).unwrap()),
+            Outcome::Wrong
+        );
+        assert_eq!(
+            sent(submit(&config, &mut log, 1, 1, "input2", "500000", 1030).unwrap()),
+            Outcome::Wrong
+        );
+        for (answer, reason) in [
+            ("600000", "600000 was already too high"),
+            ("700000", "700000 is too high, 600000 already was"),
+            ("399999", "399999 is too low, 400000 already was"),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                submit(&config, &mut log, 1, 1, "hash", answer, 1060).unwrap(),
+                Submission::Refused(reason.to_string())
+            );
+        }
+        assert_eq!(
+            sent(submit(&config, &mut log, 1, 1, "other", "700000", 1060).unwrap()),
+            Outcome::Wrong
+        );
+        assert_eq!(
+            sent(submit(&config, &mut log, 1, 1, "hash", "514579", 1060).unwrap()),
+            Outcome::Correct
+        );
+        assert!(matches!(
+            submit(&config, &mut log, 1, 1, "hash", "514579", 1100).unwrap(),
+            Submission::Refused(_)
+        ));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 6);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/2020/day/1/answer");
+        assert_eq!(requests[0].body, "level=1&answer=600000");
+        assert_eq!(requests[0].header("Cookie"), Some("session=abc"));
+
+        let reloaded: Submissions = toml::from_str(&toml::to_string(&log).unwrap()).unwrap();
+        assert_eq!(reloaded, log);
+        assert_eq!(reloaded.attempts[1].wait, Some(60));
+        assert_eq!(reloaded.attempts[2].user.as_deref(), Some("someone"));
+    }
+}